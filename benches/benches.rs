@@ -3,6 +3,10 @@
 extern crate spine;
 extern crate test;
 
+use spine::skeleton::animation::batch::BatchPose;
+use spine::skeleton::animation::pose::Pose;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
 use std::io::BufReader;
 
 #[bench]
@@ -38,3 +42,108 @@ fn animation_all(bencher: &mut test::Bencher) {
         }
     })
 }
+
+/// Allocator counting the allocations of the current thread
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|a| a.get())
+}
+
+#[bench]
+fn pose_sampling(bencher: &mut test::Bencher) {
+    let src: &[u8] = include_bytes!("../tests/example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+    let mut pose = Pose::new();
+    anim.sample(0.0, &mut pose);
+
+    let mut time = 0.0;
+    bencher.iter(|| {
+        let before = allocations();
+        anim.sample(time, &mut pose);
+        let mut count = 0;
+        for sprite in anim.sprites(&pose) {
+            count += test::black_box(sprite).attachment.len();
+        }
+        assert_eq!(allocations(), before, "sampling a pose must not allocate");
+        time = (time + 0.01) % anim.get_duration();
+        count
+    })
+}
+
+#[bench]
+fn interpolate_sprites(bencher: &mut test::Bencher) {
+    let src: &[u8] = include_bytes!("../tests/example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+
+    let mut time = 0.0;
+    bencher.iter(|| {
+        let mut count = 0;
+        for sprite in anim.interpolate(time).unwrap() {
+            count += test::black_box(sprite).attachment.len();
+        }
+        time = (time + 0.01) % anim.get_duration();
+        count
+    })
+}
+
+const INSTANCES: usize = 256;
+
+#[bench]
+fn batch_sampling(bencher: &mut test::Bencher) {
+    let src: &[u8] = include_bytes!("../tests/example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+    let times = (0..INSTANCES)
+        .map(|i| i as f32 / INSTANCES as f32 * anim.get_duration())
+        .collect::<Vec<_>>();
+    let mut batch = BatchPose::new();
+
+    bencher.iter(|| {
+        anim.sample_batch(&times, &mut batch);
+        test::black_box(&batch);
+    })
+}
+
+#[bench]
+fn per_instance_sampling(bencher: &mut test::Bencher) {
+    let src: &[u8] = include_bytes!("../tests/example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+    let times = (0..INSTANCES)
+        .map(|i| i as f32 / INSTANCES as f32 * anim.get_duration())
+        .collect::<Vec<_>>();
+    let mut poses = vec![Pose::new(); INSTANCES];
+
+    bencher.iter(|| {
+        for (pose, &time) in poses.iter_mut().zip(times.iter()) {
+            anim.sample(time, pose);
+        }
+        test::black_box(&poses);
+    })
+}
@@ -7,7 +7,7 @@ use std::io::{BufReader, Lines};
 use std::str::ParseBoolError;
 
 /// atlas texture
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Texture {
     /// name
     pub name: String,
@@ -23,18 +23,40 @@ pub struct Texture {
     pub offset: (u16, u16),
     /// index
     pub index: i16,
+    /// index of the page holding the texture
+    pub page: usize,
 }
 
-/// Iterator to parse attachments from a common image
+/// atlas page, an image holding several textures
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// image file
+    pub file: String,
+    /// size
+    pub size: (u16, u16),
+    /// format
+    pub format: String,
+    /// filter
+    pub filter: String,
+    /// repeat
+    pub repeat: String,
+}
+
+/// Iterator to parse attachments from the pages of an atlas
+///
+/// Public fields describe the first page, `pages` lists the pages read so far
 pub struct Atlas<R: Read> {
     /// file
     pub file: String,
+    /// page size
+    pub size: (u16, u16),
     /// format
     pub format: String,
     /// filter
     pub filter: String,
     /// repeat
     pub repeat: String,
+    pages: Vec<Page>,
     lines: Lines<BufReader<R>>,
 }
 
@@ -57,16 +79,15 @@ impl<R: Read> Atlas<R> {
         while let Some(line) = lines.next() {
             let line = line?;
             if !line.trim().is_empty() {
-                mapping_value(&mut lines, "size:")?;
-                let format = mapping_value(&mut lines, "format:")?;
-                let filter = mapping_value(&mut lines, "filter:")?;
-                let repeat = mapping_value(&mut lines, "repeat:")?;
-
+                let size = parse_tuple(&mapping_value(&mut lines, "size:")?)?;
+                let page = read_page(line, size, &mut lines)?;
                 return Ok(Atlas {
-                    file: line,
-                    format,
-                    filter,
-                    repeat,
+                    file: page.file.clone(),
+                    size: page.size,
+                    format: page.format.clone(),
+                    filter: page.filter.clone(),
+                    repeat: page.repeat.clone(),
+                    pages: vec![page],
                     lines,
                 });
             }
@@ -74,10 +95,27 @@ impl<R: Read> Atlas<R> {
         Err(AtlasError::Unexpected("cannot parse headers"))
     }
 
-    fn read_texture(&mut self, name: &str) -> Result<Texture, AtlasError> {
-        let rotate = {
-            let line = next_line(&mut self.lines)?;
-            line.trim_start()["rotate:".len()..].trim().parse()?
+    /// pages read so far, all of them once all textures are read
+    pub fn pages(&self) -> &[Page] {
+        &self.pages
+    }
+
+    /// reads the texture or page named `name`, `line` being the line following it
+    fn read_entry(&mut self, name: &str, line: &str) -> Result<Option<Texture>, AtlasError> {
+        let line = line.trim_start();
+        if let Some(size) = line.strip_prefix("size:") {
+            let size = parse_tuple(size)?;
+            let page = read_page(name.to_owned(), size, &mut self.lines)?;
+            self.pages.push(page);
+            return Ok(None);
+        }
+        self.read_texture(name, line).map(Some)
+    }
+
+    fn read_texture(&mut self, name: &str, rotate: &str) -> Result<Texture, AtlasError> {
+        let rotate = match rotate.get("rotate:".len()..) {
+            Some(value) => value.trim().parse()?,
+            None => return Err(AtlasError::Unexpected("unexpected mapping name")),
         };
         let mut tuples = Vec::with_capacity(4);
         for pattern in &["xy:", "size:", "orig:", "offset:"] {
@@ -96,21 +134,41 @@ impl<R: Read> Atlas<R> {
             orig: tuples[2],
             offset: tuples[3],
             index,
+            page: self.pages.len() - 1,
         })
     }
 
     fn parse_tuple(&mut self, offset: usize) -> Result<(u16, u16), AtlasError> {
         let line = next_line(&mut self.lines)?;
-        let mut tuple = Vec::with_capacity(2);
-        for s in line.trim_start()[offset..].split(',').take(2) {
-            let a = s.trim().parse()?;
-            tuple.push(a);
-        }
-        if tuple.len() != 2 {
-            Err(AtlasError::Unexpected("tuple"))
-        } else {
-            Ok((tuple[0], tuple[1]))
-        }
+        parse_tuple(&line.trim_start()[offset..])
+    }
+}
+
+/// reads the header of a page, after its size
+fn read_page<R: Read>(
+    file: String,
+    size: (u16, u16),
+    lines: &mut Lines<BufReader<R>>,
+) -> Result<Page, AtlasError> {
+    Ok(Page {
+        file,
+        size,
+        format: mapping_value(lines, "format:")?,
+        filter: mapping_value(lines, "filter:")?,
+        repeat: mapping_value(lines, "repeat:")?,
+    })
+}
+
+fn parse_tuple(text: &str) -> Result<(u16, u16), AtlasError> {
+    let mut tuple = Vec::with_capacity(2);
+    for s in text.split(',').take(2) {
+        let a = s.trim().parse()?;
+        tuple.push(a);
+    }
+    if tuple.len() != 2 {
+        Err(AtlasError::Unexpected("tuple"))
+    } else {
+        Ok((tuple[0], tuple[1]))
     }
 }
 
@@ -132,7 +190,16 @@ impl<R: Read> Iterator for Atlas<R> {
                     if name.is_empty() {
                         continue;
                     }
-                    Some(self.read_texture(name.trim()))
+                    // a page header starts with its size, a texture with its rotation
+                    let line = match next_line(&mut self.lines) {
+                        Ok(line) => line,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    match self.read_entry(name, &line) {
+                        Ok(Some(texture)) => Some(Ok(texture)),
+                        Ok(None) => continue,
+                        Err(e) => Some(Err(e)),
+                    }
                 }
                 Some(Err(e)) => Some(Err(AtlasError::from(e))),
                 None => None,
@@ -25,6 +25,8 @@ pub struct Attachment {
     pub uvs: Option<Vec<f32>>,
     pub hull: Option<i32>,
     pub edges: Option<Vec<i32>>,
+    //bounding box
+    pub vertex_count: Option<u32>,
     #[serde(default = "white_color")]
     pub color: String,
 }
@@ -39,6 +41,19 @@ pub enum AttachmentType {
     Mesh,
     RegionSequence,
     BoundingBox,
+    Unknown(String),
+}
+
+impl fmt::Display for AttachmentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AttachmentType::Region => write!(f, "region"),
+            AttachmentType::Mesh => write!(f, "mesh"),
+            AttachmentType::RegionSequence => write!(f, "regionsequence"),
+            AttachmentType::BoundingBox => write!(f, "boundingbox"),
+            AttachmentType::Unknown(ref t) => write!(f, "{}", t),
+        }
+    }
 }
 
 impl<'a> Deserialize<'a> for AttachmentType {
@@ -58,7 +73,7 @@ impl<'a> Visitor<'a> for AttachmentTypeVisitor {
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
-            "one of (region, regionsequence, boundingbox, mesh)"
+            "an attachment type (region, regionsequence, boundingbox, mesh, ...)"
         )
     }
 
@@ -68,10 +83,7 @@ impl<'a> Visitor<'a> for AttachmentTypeVisitor {
             "regionsequence" => Ok(AttachmentType::RegionSequence),
             "boundingbox" => Ok(AttachmentType::BoundingBox),
             "mesh" => Ok(AttachmentType::Mesh),
-            _ => Err(SerdeError::custom(
-                "Attachment type must be one of (region, regionsequence, boundingbox, mesh)"
-                    .to_string(),
-            )),
+            _ => Ok(AttachmentType::Unknown(value.to_owned())),
         }
     }
 
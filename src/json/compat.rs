@@ -0,0 +1,437 @@
+//! Conversions of older or newer json schemas into the one used by the loader
+//!
+//! The loader works with the spine 3.6/3.7 layout: bezier curves are stored in the `curve`
+//! property of a key as 4 values normalized between the key and the next one.
+
+use super::{
+    BoneRotateTimeline, BoneScaleTimeline, BoneTranslateTimeline, Document, SlotColorTimeline,
+    SlotTwoColorTimeline, TimelineCurve, ValueTimeline,
+};
+use rustc_hex::{FromHex, ToHex};
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
+use skeleton::timeline::bezier::{sample, solve};
+use std::collections::HashMap;
+
+/// Keyframe with a curve to the next keyframe
+trait CurveKey {
+    fn time(&self) -> f32;
+    fn curve_mut(&mut self) -> &mut Option<TimelineCurve>;
+    /// second, third and fourth control values (spine 3.8)
+    fn controls(&self) -> (Option<f32>, Option<f32>, Option<f32>);
+    /// values of all the channels of the key
+    fn channels(&self) -> Vec<f32>;
+}
+
+macro_rules! impl_curve_key {
+    ($t:ty, $channels:expr) => {
+        impl CurveKey for $t {
+            fn time(&self) -> f32 {
+                self.time
+            }
+            fn curve_mut(&mut self) -> &mut Option<TimelineCurve> {
+                &mut self.curve
+            }
+            fn controls(&self) -> (Option<f32>, Option<f32>, Option<f32>) {
+                (self.c2, self.c3, self.c4)
+            }
+            fn channels(&self) -> Vec<f32> {
+                $channels(self)
+            }
+        }
+    };
+}
+
+impl_curve_key!(BoneTranslateTimeline, |t: &BoneTranslateTimeline| vec![
+    t.x.unwrap_or(0.0),
+    t.y.unwrap_or(0.0)
+]);
+impl_curve_key!(BoneScaleTimeline, |t: &BoneScaleTimeline| vec![
+    t.x.unwrap_or(1.0),
+    t.y.unwrap_or(1.0)
+]);
+impl_curve_key!(BoneRotateTimeline, |t: &BoneRotateTimeline| vec![t
+    .angle
+    .unwrap_or(0.0)]);
+impl_curve_key!(SlotColorTimeline, |t: &SlotColorTimeline| t
+    .color
+    .as_ref()
+    .map_or_else(Vec::new, |c| color_channels(c)));
+
+impl_curve_key!(SlotTwoColorTimeline, |t: &SlotTwoColorTimeline| {
+    let mut channels = t
+        .light
+        .as_ref()
+        .map_or_else(Vec::new, |c| color_channels(c));
+    channels.extend(t.dark.as_ref().map_or_else(Vec::new, |c| color_channels(c)));
+    channels
+});
+
+impl CurveKey for ValueTimeline {
+    fn time(&self) -> f32 {
+        self.time
+    }
+    fn curve_mut(&mut self) -> &mut Option<TimelineCurve> {
+        &mut self.curve
+    }
+    fn controls(&self) -> (Option<f32>, Option<f32>, Option<f32>) {
+        (None, None, None)
+    }
+    fn channels(&self) -> Vec<f32> {
+        vec![self.value.unwrap_or(0.0)]
+    }
+}
+
+/// color channels between 0 and 1 (empty if the color is invalid)
+fn color_channels(color: &str) -> Vec<f32> {
+    color
+        .from_hex()
+        .map(|v: Vec<u8>| v.iter().map(|&c| f32::from(c) / 255.0).collect())
+        .unwrap_or_default()
+}
+
+/// Linear curve on one channel
+const LINEAR_CHANNEL: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+/// Applies `f` on all keyframes lists with curves
+fn for_each_curve_keys<F>(doc: &mut Document, mut f: F)
+where
+    F: FnMut(&mut dyn CurveKeys),
+{
+    for animation in doc.animations.iter_mut().flat_map(|a| a.values_mut()) {
+        for timeline in animation.bones.iter_mut().flat_map(|b| b.values_mut()) {
+            for keys in timeline.translate.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.rotate.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.scale.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.shear.iter_mut() {
+                f(keys);
+            }
+            let axes = vec![
+                &mut timeline.translatex,
+                &mut timeline.translatey,
+                &mut timeline.scalex,
+                &mut timeline.scaley,
+                &mut timeline.shearx,
+                &mut timeline.sheary,
+            ];
+            for keys in axes.into_iter().flat_map(|k| k.iter_mut()) {
+                f(keys);
+            }
+        }
+        for timeline in animation.slots.iter_mut().flat_map(|s| s.values_mut()) {
+            for keys in timeline.color.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.rgb.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.alpha.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.two_color.iter_mut() {
+                f(keys);
+            }
+            for keys in timeline.rgb2.iter_mut() {
+                f(keys);
+            }
+        }
+    }
+}
+
+/// List of keyframes with curves
+trait CurveKeys {
+    /// merges spine 3.8 `curve`, `c2`, `c3` and `c4` properties into `curve`
+    fn merge_controls(&mut self);
+    /// converts spine 4 absolute curves into normalized curves
+    fn normalize_curves(&mut self);
+}
+
+impl<K: CurveKey> CurveKeys for Vec<K> {
+    fn merge_controls(&mut self) {
+        for key in self.iter_mut() {
+            let (c2, c3, c4) = key.controls();
+            if let Some(TimelineCurve::CurveBezier(ref mut p)) = *key.curve_mut() {
+                if p.len() == 1 {
+                    p.extend_from_slice(&[c2.unwrap_or(0.0), c3.unwrap_or(1.0), c4.unwrap_or(1.0)]);
+                }
+            }
+        }
+    }
+
+    fn normalize_curves(&mut self) {
+        for i in 1..self.len() {
+            let (t1, next) = (self[i].time(), self[i].channels());
+            let key = &mut self[i - 1];
+            let (t0, current) = (key.time(), key.channels());
+            let dt = t1 - t0;
+            if let Some(TimelineCurve::CurveBezier(ref mut p)) = *key.curve_mut() {
+                for (c, points) in p.chunks_mut(4).enumerate() {
+                    if points.len() < 4 || dt <= 0.0 {
+                        break;
+                    }
+                    let v0 = current.get(c).cloned().unwrap_or(0.0);
+                    let dv = next.get(c).cloned().unwrap_or(0.0) - v0;
+                    points[0] = (points[0] - t0) / dt;
+                    points[2] = (points[2] - t0) / dt;
+                    if dv == 0.0 {
+                        // value does not change: any curve will do
+                        points[1] = points[0];
+                        points[3] = points[2];
+                    } else {
+                        points[1] = (points[1] - v0) / dv;
+                        points[3] = (points[3] - v0) / dv;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Upgrades a spine 3.8 document
+pub fn upgrade_3_8(doc: &mut Document) {
+    for_each_curve_keys(doc, |keys| keys.merge_controls());
+}
+
+/// Upgrades a spine 4.x document
+///
+/// Fails if the setup color of a slot, needed to convert its color timelines, is invalid
+pub fn upgrade_4(doc: &mut Document) -> Result<(), SkeletonError> {
+    // rotate keys values are stored in `value`, single value keys default to 1 for scales
+    for animation in doc.animations.iter_mut().flat_map(|a| a.values_mut()) {
+        for timeline in animation.bones.iter_mut().flat_map(|b| b.values_mut()) {
+            for key in timeline.rotate.iter_mut().flat_map(|k| k.iter_mut()) {
+                key.angle = key.angle.or(key.value);
+            }
+            let scales = timeline.scalex.iter_mut().chain(timeline.scaley.iter_mut());
+            for key in scales.flat_map(|k| k.iter_mut()) {
+                key.value = key.value.or(Some(1.0));
+            }
+        }
+    }
+
+    for_each_curve_keys(doc, |keys| keys.normalize_curves());
+
+    // `rgb`, `alpha` and `rgb2` timelines are converted into color and two color timelines,
+    // using the setup color for the missing channels. `rgb` and `alpha` timelines are merged over
+    // the union of their key times
+    let mut setup_colors = HashMap::new();
+    for (i, slot) in doc.slots.iter().flat_map(|s| s.iter()).enumerate() {
+        if let Some(ref color) = slot.color {
+            let color =
+                Color::from_rgba_hex(color).map_err(|e| e.at(&format!("slots[{}].color", i)))?;
+            setup_colors.insert(slot.name.clone(), color);
+        }
+    }
+    for animation in doc.animations.iter_mut().flat_map(|a| a.values_mut()) {
+        for (name, timeline) in animation.slots.iter_mut().flat_map(|s| s.iter_mut()) {
+            let setup = setup_colors.get(name).cloned().unwrap_or(Color::WHITE);
+            if let Some(rgb2) = timeline.rgb2.take() {
+                if timeline.two_color.is_none() {
+                    let alpha = format!("{:02x}", setup.to_rgba8()[3]);
+                    timeline.two_color = Some(
+                        rgb2.into_iter()
+                            .map(|mut key| {
+                                key.light = key.light.map(|c| format!("{}{}", c, alpha));
+                                if let Some(TimelineCurve::CurveBezier(ref mut p)) = key.curve {
+                                    if p.len() > 12 {
+                                        // linear light alpha, between light rgb and dark rgb
+                                        let dark = p.split_off(12);
+                                        p.extend_from_slice(&LINEAR_CHANNEL);
+                                        p.extend(dark);
+                                    }
+                                }
+                                key
+                            })
+                            .collect(),
+                    );
+                }
+            }
+            if timeline.color.is_some() {
+                continue;
+            }
+            let rgb = timeline.rgb.take().map(|keys| {
+                keys.into_iter()
+                    .map(|key| {
+                        let channels = key.channels();
+                        (key.time, channels, key.curve)
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let alpha = timeline.alpha.take().map(|keys| {
+                keys.into_iter()
+                    .map(|key| (key.time, key.channels(), key.curve))
+                    .collect::<Vec<_>>()
+            });
+            if rgb.is_some() || alpha.is_some() {
+                let rgb = rgb.unwrap_or_else(|| vec![(0.0, vec![setup.r, setup.g, setup.b], None)]);
+                let alpha = alpha.unwrap_or_else(|| vec![(0.0, vec![setup.a], None)]);
+                timeline.color = Some(merge_color_keys(&rgb, &alpha));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Keyframes of some channels: time, values and curve to the next key
+type ChannelKeys = Vec<(f32, Vec<f32>, Option<TimelineCurve>)>;
+
+/// Merges rgb and alpha keys into color keys at the union of their key times
+///
+/// Bezier curves are split at the inserted times, so the merged timeline interpolates exactly
+/// as the two original ones. A stepped change gets an extra key holding the previous values.
+fn merge_color_keys(rgb: &ChannelKeys, alpha: &ChannelKeys) -> Vec<SlotColorTimeline> {
+    let mut times = rgb
+        .iter()
+        .chain(alpha.iter())
+        .map(|k| k.0)
+        .collect::<Vec<_>>();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+    times.dedup();
+
+    let key = |values: Vec<f32>, time: f32, curve: Option<TimelineCurve>| {
+        let color = values
+            .iter()
+            .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect::<Vec<_>>();
+        SlotColorTimeline {
+            time,
+            color: Some(color.to_hex()),
+            curve,
+            c2: None,
+            c3: None,
+            c4: None,
+        }
+    };
+    let mut keys = Vec::with_capacity(times.len());
+    for (i, &time) in times.iter().enumerate() {
+        let mut values = channel_values(rgb, time);
+        values.extend(channel_values(alpha, time));
+        if i > 0 {
+            let mut previous = channel_left_values(rgb, time);
+            previous.extend(channel_left_values(alpha, time));
+            if previous != values {
+                keys.push(key(previous, time, None));
+            }
+        }
+        let curve = times.get(i + 1).and_then(|&next| {
+            let (rgb, alpha) = (
+                channel_curves(rgb, time, next),
+                channel_curves(alpha, time, next),
+            );
+            if rgb.is_none() && alpha.is_none() {
+                return None;
+            }
+            let mut curve = Vec::with_capacity(16);
+            curve.extend(rgb.unwrap_or_else(|| [LINEAR_CHANNEL; 3].concat()));
+            curve.extend(alpha.unwrap_or_else(|| LINEAR_CHANNEL.to_vec()));
+            Some(TimelineCurve::CurveBezier(curve))
+        });
+        keys.push(key(values, time, curve));
+    }
+    keys
+}
+
+/// Index of the segment starting at or before `time`, if it has a next key
+fn channel_segment(keys: &ChannelKeys, time: f32) -> Option<usize> {
+    let i = keys.iter().take_while(|k| k.0 <= time).count();
+    if i == 0 || i == keys.len() {
+        None
+    } else {
+        Some(i - 1)
+    }
+}
+
+/// Values of the channels at `time`
+fn channel_values(keys: &ChannelKeys, time: f32) -> Vec<f32> {
+    let i = match channel_segment(keys, time) {
+        Some(i) => i,
+        None if keys.first().is_some_and(|k| time < k.0) => return keys[0].1.clone(),
+        None => return keys.last().map_or_else(Vec::new, |k| k.1.clone()),
+    };
+    let ((t0, ref v0, ref curve), (t1, ref v1, _)) = (&keys[i], &keys[i + 1]);
+    let percent = (time - t0) / (t1 - t0);
+    v0.iter()
+        .zip(v1.iter())
+        .enumerate()
+        .map(|(c, (&v0, &v1))| {
+            let percent = match *curve {
+                Some(TimelineCurve::CurveStepped) => 0.0,
+                Some(TimelineCurve::CurveBezier(ref p)) if p.len() >= 4 * c + 4 => {
+                    let p = &p[4 * c..4 * c + 4];
+                    sample(solve(percent, p[0], p[2]), p[1], p[3])
+                }
+                _ => percent,
+            };
+            v0 + (v1 - v0) * percent
+        })
+        .collect()
+}
+
+/// Values of the channels just before `time`, which differ from `channel_values` on stepped keys
+fn channel_left_values(keys: &ChannelKeys, time: f32) -> Vec<f32> {
+    match keys.iter().position(|k| k.0 == time) {
+        Some(i) if i > 0 => match keys[i - 1].2 {
+            Some(TimelineCurve::CurveStepped) => keys[i - 1].1.clone(),
+            _ => keys[i].1.clone(),
+        },
+        _ => channel_values(keys, time),
+    }
+}
+
+/// Normalized bezier curves of the channels between `start` and `end`, `None` if linear
+fn channel_curves(keys: &ChannelKeys, start: f32, end: f32) -> Option<Vec<f32>> {
+    let i = channel_segment(keys, start)?;
+    let ((t0, _, ref curve), (t1, _, _)) = (&keys[i], &keys[i + 1]);
+    let p = match *curve {
+        Some(TimelineCurve::CurveBezier(ref p)) => p,
+        _ => return None,
+    };
+    let (u0, u1) = ((start - t0) / (t1 - t0), (end - t0) / (t1 - t0));
+    let mut curves = Vec::with_capacity(p.len());
+    for p in p.chunks(4).filter(|p| p.len() == 4) {
+        let (s0, s1) = (solve(u0, p[0], p[2]), solve(u1, p[0], p[2]));
+        let x = split_bezier([0.0, p[0], p[2], 1.0], s0, s1);
+        let y = split_bezier([0.0, p[1], p[3], 1.0], s0, s1);
+        let (dx, dy) = (x[3] - x[0], y[3] - y[0]);
+        if dx <= 0.0 {
+            curves.extend_from_slice(&LINEAR_CHANNEL);
+            continue;
+        }
+        let (cx1, cx2) = ((x[1] - x[0]) / dx, (x[2] - x[0]) / dx);
+        if dy == 0.0 {
+            // value does not change: any curve will do
+            curves.extend_from_slice(&[cx1, cx1, cx2, cx2]);
+        } else {
+            curves.extend_from_slice(&[cx1, (y[1] - y[0]) / dy, cx2, (y[2] - y[0]) / dy]);
+        }
+    }
+    Some(curves)
+}
+
+/// Control values of the part of a cubic bezier between parameters `s0` and `s1`
+fn split_bezier(p: [f32; 4], s0: f32, s1: f32) -> [f32; 4] {
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    // part before s1
+    let (a, b, c) = (
+        lerp(p[0], p[1], s1),
+        lerp(p[1], p[2], s1),
+        lerp(p[2], p[3], s1),
+    );
+    let (d, e) = (lerp(a, b, s1), lerp(b, c, s1));
+    let p = [p[0], a, d, lerp(d, e, s1)];
+    // part of it after s0
+    let t = if s1 > 0.0 { s0 / s1 } else { 0.0 };
+    let (a, b, c) = (
+        lerp(p[0], p[1], t),
+        lerp(p[1], p[2], t),
+        lerp(p[2], p[3], t),
+    );
+    let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+    [lerp(d, e, t), e, c, p[3]]
+}
@@ -0,0 +1,138 @@
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// Member order of the objects of a json document, as written in the document
+///
+/// `serde_json::Value` sorts object members, the layout is used to write them back in order.
+#[derive(Debug, Clone)]
+pub enum Layout {
+    /// any value which is neither an object nor an array
+    Leaf,
+    Array(Vec<Layout>),
+    Object(Vec<(String, Layout)>),
+}
+
+impl Layout {
+    /// `value` serialized with the member order of the layout
+    ///
+    /// Members missing from the layout follow the others, array items without layout use the
+    /// layout of the last item.
+    pub fn apply<'a>(&'a self, value: &'a Value) -> Ordered<'a> {
+        Ordered {
+            value,
+            layout: self,
+        }
+    }
+}
+
+/// Value serialized with the member order of a layout
+pub struct Ordered<'a> {
+    value: &'a Value,
+    layout: &'a Layout,
+}
+
+impl<'a> Serialize for Ordered<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match (self.value, self.layout) {
+            (Value::Object(map), Layout::Object(members)) => {
+                let mut s = serializer.serialize_map(Some(map.len()))?;
+                for (i, (name, layout)) in members.iter().enumerate() {
+                    // a duplicated member is only written once
+                    if members[..i].iter().any(|m| m.0 == *name) {
+                        continue;
+                    }
+                    if let Some(value) = map.get(name) {
+                        s.serialize_entry(name, &layout.apply(value))?;
+                    }
+                }
+                for (name, value) in map {
+                    if !members.iter().any(|m| m.0 == *name) {
+                        s.serialize_entry(name, value)?;
+                    }
+                }
+                s.end()
+            }
+            (Value::Array(values), Layout::Array(items)) => {
+                let mut s = serializer.serialize_seq(Some(values.len()))?;
+                for (i, value) in values.iter().enumerate() {
+                    match items.get(i).or_else(|| items.last()) {
+                        Some(layout) => s.serialize_element(&layout.apply(value))?,
+                        None => s.serialize_element(value)?,
+                    }
+                }
+                s.end()
+            }
+            (value, _) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for Layout {
+    fn deserialize<D>(deserializer: D) -> Result<Layout, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_any(LayoutVisitor)
+    }
+}
+
+struct LayoutVisitor;
+
+impl<'a> Visitor<'a> for LayoutVisitor {
+    type Value = Layout;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "any json value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_unit<E>(self) -> Result<Layout, E> {
+        Ok(Layout::Leaf)
+    }
+
+    fn visit_seq<S>(self, mut visitor: S) -> Result<Layout, S::Error>
+    where
+        S: SeqAccess<'a>,
+    {
+        let mut items = Vec::new();
+        while let Some(item) = visitor.next_element()? {
+            items.push(item);
+        }
+        Ok(Layout::Array(items))
+    }
+
+    fn visit_map<M>(self, mut visitor: M) -> Result<Layout, M::Error>
+    where
+        M: MapAccess<'a>,
+    {
+        let mut members = Vec::new();
+        while let Some(member) = visitor.next_entry()? {
+            members.push(member);
+        }
+        Ok(Layout::Object(members))
+    }
+}
@@ -1,16 +1,41 @@
 mod attachment;
+pub mod compat;
+mod layout;
+mod skins;
 mod timeline_curve;
 
 pub use self::attachment::{Attachment, AttachmentType};
+pub use self::layout::Layout;
+pub use self::skins::Skins;
 pub use self::timeline_curve::TimelineCurve;
 use std::collections::HashMap;
 
+/// skin attachments: key: slot name, value: attachments by name
+pub type Skin = HashMap<String, HashMap<String, Attachment>>;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Document {
+    pub skeleton: Option<SkeletonHeader>,
     pub bones: Option<Vec<Bone>>,
     pub slots: Option<Vec<Slot>>,
-    pub skins: Option<HashMap<String, HashMap<String, HashMap<String, Attachment>>>>,
+    pub skins: Option<Skins>,
     pub animations: Option<HashMap<String, Animation>>,
+    pub ik: Option<Vec<Constraint>>,
+    pub transform: Option<Vec<Constraint>>,
+    pub path: Option<Vec<Constraint>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SkeletonHeader {
+    pub hash: Option<String>,
+    pub spine: Option<String>,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub fps: Option<f32>,
+    pub images: Option<String>,
+    pub audio: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -26,7 +51,18 @@ pub struct Bone {
     pub rotation: Option<f32>,
     pub inherit_scale: Option<bool>,
     pub inherit_rotation: Option<bool>,
+    #[serde(alias = "inherit")]
     pub transform: Option<String>,
+    /// bone is only active when a skin requiring it is applied
+    pub skin: Option<bool>,
+}
+
+/// ik, transform or path constraint, only the bones it uses
+#[derive(Debug, Clone, Deserialize)]
+pub struct Constraint {
+    pub name: String,
+    pub bones: Option<Vec<String>>,
+    pub target: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -34,9 +70,12 @@ pub struct Slot {
     pub name: String,
     pub bone: String,
     pub color: Option<String>,
+    pub dark: Option<String>,
     pub attachment: Option<String>,
+    pub blend: Option<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub struct Event {
     pub name: String,
@@ -50,35 +89,59 @@ pub struct Animation {
     pub bones: Option<HashMap<String, BoneTimeline>>,
     pub slots: Option<HashMap<String, SlotTimeline>>,
     pub events: Option<Vec<EventKeyframe>>,
+    #[serde(alias = "drawOrder")]
     pub draworder: Option<Vec<DrawOrderTimeline>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct BoneTimeline {
     pub translate: Option<Vec<BoneTranslateTimeline>>,
+    pub translatex: Option<Vec<ValueTimeline>>,
+    pub translatey: Option<Vec<ValueTimeline>>,
     pub rotate: Option<Vec<BoneRotateTimeline>>,
     pub scale: Option<Vec<BoneScaleTimeline>>,
+    pub scalex: Option<Vec<ValueTimeline>>,
+    pub scaley: Option<Vec<ValueTimeline>>,
+    pub shear: Option<Vec<BoneShearTimeline>>,
+    pub shearx: Option<Vec<ValueTimeline>>,
+    pub sheary: Option<Vec<ValueTimeline>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct BoneTranslateTimeline {
+    #[serde(default)]
     pub time: f32,
     pub curve: Option<TimelineCurve>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub c4: Option<f32>,
     pub x: Option<f32>,
     pub y: Option<f32>,
 }
 
+/// shear keys have the same layout as translate keys
+pub type BoneShearTimeline = BoneTranslateTimeline;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct BoneRotateTimeline {
+    #[serde(default)]
     pub time: f32,
     pub curve: Option<TimelineCurve>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub c4: Option<f32>,
     pub angle: Option<f32>,
+    pub value: Option<f32>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct BoneScaleTimeline {
+    #[serde(default)]
     pub time: f32,
     pub curve: Option<TimelineCurve>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub c4: Option<f32>,
     pub x: Option<f32>,
     pub y: Option<f32>,
 }
@@ -86,24 +149,59 @@ pub struct BoneScaleTimeline {
 #[derive(Debug, Clone, Deserialize)]
 pub struct SlotTimeline {
     pub attachment: Option<Vec<SlotAttachmentTimeline>>,
+    #[serde(alias = "rgba")]
     pub color: Option<Vec<SlotColorTimeline>>,
+    pub rgb: Option<Vec<SlotColorTimeline>>,
+    pub alpha: Option<Vec<ValueTimeline>>,
+    #[serde(rename = "twoColor", alias = "rgba2")]
+    pub two_color: Option<Vec<SlotTwoColorTimeline>>,
+    pub rgb2: Option<Vec<SlotTwoColorTimeline>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SlotAttachmentTimeline {
+    #[serde(default)]
     pub time: f32,
     pub name: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SlotColorTimeline {
+    #[serde(default)]
     pub time: f32,
     pub color: Option<String>,
     pub curve: Option<TimelineCurve>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub c4: Option<f32>,
+}
+
+/// light and dark colors keys
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlotTwoColorTimeline {
+    #[serde(default)]
+    pub time: f32,
+    pub light: Option<String>,
+    pub dark: Option<String>,
+    pub curve: Option<TimelineCurve>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub c4: Option<f32>,
+}
+
+/// single value keys (spine 4)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ValueTimeline {
+    #[serde(default)]
+    pub time: f32,
+    pub value: Option<f32>,
+    pub curve: Option<TimelineCurve>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub struct EventKeyframe {
+    #[serde(default)]
     pub time: f32,
     name: String,
     int: Option<i32>,
@@ -111,12 +209,15 @@ pub struct EventKeyframe {
     string: Option<String>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub struct DrawOrderTimeline {
+    #[serde(default)]
     pub time: f32,
     offsets: Option<Vec<DrawOrderTimelineOffset>>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 pub struct DrawOrderTimelineOffset {
     slot: String,
@@ -0,0 +1,81 @@
+use super::Skin;
+use serde::de::{
+    value::{MapAccessDeserializer, SeqAccessDeserializer},
+    MapAccess, SeqAccess, Visitor,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Skins, either as a map by name (up to 3.7) or as an array (3.8+)
+#[derive(Debug, Clone)]
+pub enum Skins {
+    Map(HashMap<String, Skin>),
+    Array(Vec<NamedSkin>),
+}
+
+/// Skin of the 3.8+ layout, with the bones and constraints it requires
+#[derive(Debug, Clone, Deserialize)]
+pub struct NamedSkin {
+    pub name: String,
+    pub attachments: Option<Skin>,
+    pub bones: Option<Vec<String>>,
+    pub ik: Option<Vec<String>>,
+    pub transform: Option<Vec<String>>,
+    pub path: Option<Vec<String>>,
+}
+
+impl Skins {
+    /// Converts skins into the array layout
+    pub fn into_named(self) -> Vec<NamedSkin> {
+        match self {
+            Skins::Map(skins) => skins
+                .into_iter()
+                .map(|(name, attachments)| NamedSkin {
+                    name,
+                    attachments: Some(attachments),
+                    bones: None,
+                    ik: None,
+                    transform: None,
+                    path: None,
+                })
+                .collect(),
+            Skins::Array(skins) => skins,
+        }
+    }
+}
+
+impl<'a> Deserialize<'a> for Skins {
+    fn deserialize<D>(deserializer: D) -> Result<Skins, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_any(SkinsVisitor)
+    }
+}
+
+struct SkinsVisitor;
+
+impl<'a> Visitor<'a> for SkinsVisitor {
+    type Value = Skins;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "map or array of skins")
+    }
+
+    fn visit_map<M>(self, visitor: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'a>,
+    {
+        let map = Deserialize::deserialize(MapAccessDeserializer::new(visitor))?;
+        Ok(Skins::Map(map))
+    }
+
+    fn visit_seq<S>(self, visitor: S) -> Result<Self::Value, S::Error>
+    where
+        S: SeqAccess<'a>,
+    {
+        let array = Deserialize::deserialize(SeqAccessDeserializer::new(visitor))?;
+        Ok(Skins::Array(array))
+    }
+}
@@ -2,6 +2,7 @@ use serde::de::{value::SeqAccessDeserializer, Error as SerdeError, SeqAccess, Vi
 use serde::{Deserialize, Deserializer};
 use std::fmt;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum TimelineCurve {
     CurveLinear,
@@ -24,7 +25,32 @@ impl<'a> Visitor<'a> for TimelineCurveVisitor {
     type Value = TimelineCurve;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        write!(formatter, "array of floats or one of (linear, stepped)")
+        write!(
+            formatter,
+            "float, array of floats or one of (linear, stepped)"
+        )
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+    where
+        E: SerdeError,
+    {
+        // spine 3.8: first control point, others are stored in c2, c3 and c4
+        Ok(TimelineCurve::CurveBezier(vec![value as f32]))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: SerdeError,
+    {
+        self.visit_f64(value as f64)
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: SerdeError,
+    {
+        self.visit_f64(value as f64)
     }
 
     fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -34,9 +60,9 @@ impl<'a> Visitor<'a> for TimelineCurveVisitor {
         match value {
             "linear" => Ok(TimelineCurve::CurveLinear),
             "stepped" => Ok(TimelineCurve::CurveStepped),
-            _ => Err(SerdeError::custom(format!(
-                "Timeline curve must be either linear, stepped or an array"
-            ))),
+            _ => Err(SerdeError::custom(
+                "Timeline curve must be either linear, stepped or an array".to_string(),
+            )),
         }
     }
 
@@ -16,6 +16,9 @@
 //!     .unwrap();
 //! ```
 //!
+//! `skeleton::loader::SkeletonLoader` gives more control on loading (scale, sections to skip,
+//! attachment callback ...).
+//!
 //! ## Step 2: preparing for drawing
 //!
 //! You can retreive the list of animations and skins provided a document:
@@ -43,10 +46,13 @@
 //! You then have 2 methods to get the sprites you need to draw:
 //! - directly call `animation.interpolate` for a given time
 //! - use a built-in `AnimationIter` iterator by calling `animation.run()` to run the animation
-//! with a constant period
+//!   with a constant period
 //!
 //! Both methods returns a `Sprites` iterator over the `Sprite`s do be drawn.
 //!
+//! To avoid allocating on every frame, `animation.sample` writes the pose into a reusable
+//! `skeleton::animation::pose::Pose` and `animation.sprites` iterates over its sprites.
+//!
 //! ```no_run
 //! # let skeleton: spine::skeleton::Skeleton = unsafe { std::mem::uninitialized() };
 //! let animation = skeleton.get_animated_skin("default", Some("walk")).unwrap();
@@ -83,6 +89,7 @@ extern crate serde_derive;
 extern crate rustc_hex;
 extern crate serde;
 extern crate serde_json;
+extern crate serde_path_to_error;
 
 pub mod atlas;
 mod json;
@@ -0,0 +1,242 @@
+//! Module to precompute animations at a fixed rate, for cheap playback
+//!
+//! A `BakedAnimation` (see `SkinAnimation::bake`) holds the world srt of every bone and the
+//! color and attachment of every slot, sampled at a fixed number of frames per second.
+//! Playing it back only interpolates linearly between samples. It can be serialized with serde.
+
+use skeleton::color::Color;
+use skeleton::reduce::{self, Tolerance};
+use skeleton::srt::SRT;
+use skeleton::timeline::curve::keys_before;
+use std::f32::consts::TAU;
+
+/// Animation sampled at a fixed rate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BakedAnimation {
+    /// samples per second
+    pub fps: f32,
+    /// duration of the animation, in seconds
+    pub duration: f32,
+    /// world srts of all skeleton bones, indexed by bone
+    pub bones: Vec<BoneTrack>,
+    /// colors and attachments of the slots drawn by the skin
+    pub slots: Vec<SlotTrack>,
+}
+
+/// World srt samples of a bone
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BoneTrack {
+    /// frame of each sample
+    pub frames: Vec<u32>,
+    /// x, y, rotation (radians, continuous from sample to sample), scale x, scale y
+    pub values: Vec<[f32; 5]>,
+}
+
+/// Color and attachment samples of a slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlotTrack {
+    /// slot name
+    pub slot: String,
+    /// frame of each color sample
+    pub color_frames: Vec<u32>,
+    /// slot colors (r, g, b, a), without skeleton and attachment colors
+    pub colors: Vec<[f32; 4]>,
+    /// dark colors (r, g, b) at the same frames as `colors`, empty if the slot does not use two
+    /// color tinting
+    #[serde(default)]
+    pub dark_colors: Vec<[f32; 3]>,
+    /// frames where the attachment changes, with the name of the drawn attachment
+    pub attachments: Vec<(u32, Option<String>)>,
+}
+
+impl BakedAnimation {
+    /// frame (with its fractional part) shown at `time`, the last frame being held
+    pub fn frame(&self, time: f32) -> f32 {
+        time.max(0.0) * self.fps
+    }
+
+    /// computes world srts of all bones at `time` into `srts`, reusing its capacity
+    pub fn srts(&self, time: f32, srts: &mut Vec<SRT>) {
+        let frame = self.frame(time);
+        srts.clear();
+        srts.extend(self.bones.iter().map(|track| track.srt(frame)));
+    }
+
+    /// finds the track of a slot
+    pub fn slot(&self, name: &str) -> Option<&SlotTrack> {
+        self.slots.iter().find(|track| track.slot == name)
+    }
+}
+
+/// `values` interpolated at `frame`, `values` being sampled at `frames`
+///
+/// `None` if the values around `frame` are missing, e.g. from a deserialized track whose
+/// `values` are shorter than its `frames`
+fn interpolate<T: Copy, F: Fn(&T, &T, f32) -> T>(
+    frames: &[u32],
+    values: &[T],
+    frame: f32,
+    lerp: F,
+) -> Option<T> {
+    let count = keys_before(frames, |&f| f as f32, frame);
+    match (count, frames.get(count)) {
+        (0, _) => values.first().cloned(),
+        (count, Some(&next)) => {
+            let from = frames[count - 1] as f32;
+            let time = (frame - from) / (next as f32 - from);
+            Some(lerp(values.get(count - 1)?, values.get(count)?, time))
+        }
+        (count, None) => values.get(count - 1).cloned(),
+    }
+}
+
+impl BoneTrack {
+    /// world srt at `frame`
+    pub fn srt(&self, frame: f32) -> SRT {
+        let v = interpolate(&self.frames, &self.values, frame, |a, b, t| {
+            let mut v = [0.0; 5];
+            for i in 0..5 {
+                v[i] = reduce::lerp(a[i], b[i], t);
+            }
+            v
+        })
+        .unwrap_or([0.0, 0.0, 0.0, 1.0, 1.0]);
+        SRT {
+            position: [v[0], v[1]],
+            rotation: v[2],
+            scale: [v[3], v[4]],
+            cos: v[2].cos(),
+            sin: v[2].sin(),
+        }
+    }
+
+    /// adds a sample, unwrapping its rotation to stay close to the previous one
+    pub(crate) fn push(&mut self, frame: u32, srt: &SRT) {
+        let mut rotation = srt.rotation;
+        if let Some(previous) = self.values.last() {
+            rotation -= ((rotation - previous[2]) / TAU).round() * TAU;
+        }
+        self.frames.push(frame);
+        self.values.push([
+            srt.position[0],
+            srt.position[1],
+            rotation,
+            srt.scale[0],
+            srt.scale[1],
+        ]);
+    }
+
+    /// drops the samples recovered by interpolation within `tolerance`
+    pub(crate) fn reduce(&mut self, tolerance: &Tolerance) {
+        let limits = [
+            tolerance.translation,
+            tolerance.translation,
+            tolerance.rotation.to_radians(),
+            tolerance.scale,
+            tolerance.scale,
+        ];
+        let (frames, values) = (&self.frames, &self.values);
+        let kept = reduce::reduce(frames.len(), |from, to| {
+            (from + 1..to).all(|i| {
+                let t = (frames[i] - frames[from]) as f32 / (frames[to] - frames[from]) as f32;
+                (0..5).all(|c| {
+                    let error = reduce::lerp(values[from][c], values[to][c], t) - values[i][c];
+                    error.abs() <= limits[c]
+                })
+            })
+        });
+        self.frames = kept.iter().map(|&i| self.frames[i]).collect();
+        self.values = kept.iter().map(|&i| self.values[i]).collect();
+    }
+}
+
+impl SlotTrack {
+    /// creates an empty track
+    pub(crate) fn new(slot: &str) -> SlotTrack {
+        SlotTrack {
+            slot: slot.to_owned(),
+            color_frames: Vec::new(),
+            colors: Vec::new(),
+            dark_colors: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// slot color at `frame`
+    pub fn color(&self, frame: f32) -> Color {
+        interpolate(&self.color_frames, &self.colors, frame, |a, b, t| {
+            let mut c = [0.0; 4];
+            for i in 0..4 {
+                c[i] = reduce::lerp(a[i], b[i], t);
+            }
+            c
+        })
+        .map_or(Color::WHITE, |c| Color::new(c[0], c[1], c[2], c[3]))
+    }
+
+    /// dark color at `frame`, `None` if the slot does not use two color tinting
+    pub fn dark_color(&self, frame: f32) -> Option<Color> {
+        interpolate(&self.color_frames, &self.dark_colors, frame, |a, b, t| {
+            let mut c = [0.0; 3];
+            for i in 0..3 {
+                c[i] = reduce::lerp(a[i], b[i], t);
+            }
+            c
+        })
+        .map(|c| Color::new(c[0], c[1], c[2], 1.0))
+    }
+
+    /// name of the attachment drawn at `frame`, `None` if nothing is drawn
+    pub fn attachment(&self, frame: f32) -> Option<&str> {
+        let count = keys_before(&self.attachments, |a| a.0 as f32, frame).max(1);
+        self.attachments.get(count - 1).and_then(|a| a.1.as_deref())
+    }
+
+    /// adds a sample, attachments are only added when they change
+    pub(crate) fn push(
+        &mut self,
+        frame: u32,
+        color: Color,
+        dark_color: Option<Color>,
+        attachment: Option<&str>,
+    ) {
+        self.color_frames.push(frame);
+        self.colors.push([color.r, color.g, color.b, color.a]);
+        // a slot gets two color tinting from its first dark color, black before
+        match dark_color {
+            Some(dark) => {
+                self.dark_colors.resize(self.colors.len() - 1, [0.0; 3]);
+                self.dark_colors.push([dark.r, dark.g, dark.b]);
+            }
+            None if !self.dark_colors.is_empty() => self.dark_colors.push([0.0; 3]),
+            None => (),
+        }
+        if self.attachments.last().map(|a| a.1.as_deref()) != Some(attachment) {
+            self.attachments
+                .push((frame, attachment.map(|n| n.to_owned())));
+        }
+    }
+
+    /// drops the color samples recovered by interpolation within `tolerance`, on both colors
+    pub(crate) fn reduce(&mut self, tolerance: &Tolerance) {
+        let (frames, colors, darks) = (&self.color_frames, &self.colors, &self.dark_colors);
+        let kept = reduce::reduce(frames.len(), |from, to| {
+            (from + 1..to).all(|i| {
+                let t = (frames[i] - frames[from]) as f32 / (frames[to] - frames[from]) as f32;
+                let close = |from: &[f32], to: &[f32], value: &[f32]| {
+                    (0..value.len()).all(|c| {
+                        let error = reduce::lerp(from[c], to[c], t) - value[c];
+                        error.abs() <= tolerance.color
+                    })
+                };
+                close(&colors[from], &colors[to], &colors[i])
+                    && (darks.is_empty() || close(&darks[from], &darks[to], &darks[i]))
+            })
+        });
+        self.color_frames = kept.iter().map(|&i| self.color_frames[i]).collect();
+        self.colors = kept.iter().map(|&i| self.colors[i]).collect();
+        if !self.dark_colors.is_empty() {
+            self.dark_colors = kept.iter().map(|&i| self.dark_colors[i]).collect();
+        }
+    }
+}
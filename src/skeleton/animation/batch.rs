@@ -0,0 +1,274 @@
+//! Module to evaluate an animation for many instances of a skeleton at once
+//!
+//! Values are stored as a structure of arrays: for each bone, the values of all the instances
+//! are contiguous. Timelines are still interpolated one instance after the other (keys being
+//! searched with a cursor, fastest with sorted times). Only the world transforms are computed
+//! several instances at a time (with SSE on x86_64, scalar elsewhere).
+
+use skeleton::bone::Bone;
+use skeleton::srt::SRT;
+use skeleton::timeline::BoneTimeline;
+
+/// World srts of all bones of several instances, reused from frame to frame
+///
+/// ```no_run
+/// # let skeleton: spine::skeleton::Skeleton = unsafe { std::mem::uninitialized() };
+/// use spine::skeleton::animation::batch::BatchPose;
+///
+/// let animation = skeleton.get_animated_skin("default", Some("walk")).unwrap();
+/// let times = (0..100).map(|i| i as f32 * 0.01).collect::<Vec<_>>();
+/// let mut batch = BatchPose::new();
+/// animation.sample_batch(&times, &mut batch);
+/// let srt = batch.srt(42, 0); // root bone of the instance at time 0.42
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BatchPose {
+    instances: usize,
+    bones: usize,
+    x: Vec<f32>,
+    y: Vec<f32>,
+    rotation: Vec<f32>,
+    scale_x: Vec<f32>,
+    scale_y: Vec<f32>,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+}
+
+/// World transforms of the parent bone, one value per instance
+struct Parent<'a> {
+    x: &'a [f32],
+    y: &'a [f32],
+    scale_x: &'a [f32],
+    scale_y: &'a [f32],
+    cos: &'a [f32],
+    sin: &'a [f32],
+}
+
+impl BatchPose {
+    /// creates an empty batch, its buffers grow on the first sampling only
+    pub fn new() -> BatchPose {
+        BatchPose::default()
+    }
+
+    /// number of instances of the last sampling
+    pub fn instances(&self) -> usize {
+        self.instances
+    }
+
+    /// number of bones of each instance
+    pub fn bones(&self) -> usize {
+        self.bones
+    }
+
+    /// world srt of a bone of an instance
+    pub fn srt(&self, instance: usize, bone: usize) -> SRT {
+        let i = bone * self.instances + instance;
+        SRT {
+            scale: [self.scale_x[i], self.scale_y[i]],
+            rotation: self.rotation[i],
+            position: [self.x[i], self.y[i]],
+            cos: self.cos[i],
+            sin: self.sin[i],
+        }
+    }
+
+    /// world positions (x, y) of a bone, indexed by instance
+    pub fn positions(&self, bone: usize) -> (&[f32], &[f32]) {
+        let range = bone * self.instances..(bone + 1) * self.instances;
+        (&self.x[range.clone()], &self.y[range])
+    }
+
+    /// resizes buffers for `bones` bones of `instances` instances
+    pub(crate) fn reset(&mut self, bones: usize, instances: usize) {
+        self.bones = bones;
+        self.instances = instances;
+        for v in &mut [
+            &mut self.x,
+            &mut self.y,
+            &mut self.rotation,
+            &mut self.scale_x,
+            &mut self.scale_y,
+            &mut self.cos,
+            &mut self.sin,
+        ] {
+            v.resize(bones * instances, 0.0);
+        }
+    }
+
+    /// computes the world srts of bone `index` for all instances, its parent being already
+    /// computed
+    ///
+    /// Mirrors `SkinAnimation::sample`, `root` being the position and scale of the skeleton
+    pub(crate) fn sample_bone(
+        &mut self,
+        index: usize,
+        bone: &Bone,
+        active: bool,
+        anim: Option<&BoneTimeline>,
+        times: &[f32],
+        root: ([f32; 2], [f32; 2]),
+    ) {
+        let n = self.instances;
+        let range = index * n..(index + 1) * n;
+        let setup = &bone.srt;
+
+        // inactive bones keep their setup pose
+        if !active {
+            fill(&mut self.x[range.clone()], setup.position[0]);
+            fill(&mut self.y[range.clone()], setup.position[1]);
+            fill(&mut self.rotation[range.clone()], setup.rotation);
+            fill(&mut self.scale_x[range.clone()], setup.scale[0]);
+            fill(&mut self.scale_y[range.clone()], setup.scale[1]);
+            fill(&mut self.cos[range.clone()], setup.cos);
+            fill(&mut self.sin[range], setup.sin);
+            return;
+        }
+
+        // local srts: setup pose with animation
+        {
+            let (x, y) = (&mut self.x[range.clone()], &mut self.y[range.clone()]);
+            let rotation = &mut self.rotation[range.clone()];
+            let scale_x = &mut self.scale_x[range.clone()];
+            let scale_y = &mut self.scale_y[range.clone()];
+            match anim {
+                Some(anim) => anim.srt_batch(times, (x, y), rotation, (scale_x, scale_y)),
+                None => {
+                    fill(x, 0.0);
+                    fill(y, 0.0);
+                    fill(rotation, 0.0);
+                    fill(scale_x, 1.0);
+                    fill(scale_y, 1.0);
+                }
+            }
+            for j in 0..n {
+                x[j] += setup.position[0];
+                y[j] += setup.position[1];
+                rotation[j] += setup.rotation;
+                scale_x[j] *= setup.scale[0];
+                scale_y[j] *= setup.scale[1];
+            }
+        }
+
+        // inherit world from parent srt, or from the skeleton root
+        match bone.parent_index {
+            Some(parent) => {
+                let (px, x) = parent_child(&mut self.x, parent, index, n);
+                let (py, y) = parent_child(&mut self.y, parent, index, n);
+                let (protation, rotation) = parent_child(&mut self.rotation, parent, index, n);
+                let (pscale_x, scale_x) = parent_child(&mut self.scale_x, parent, index, n);
+                let (pscale_y, scale_y) = parent_child(&mut self.scale_y, parent, index, n);
+                let (pcos, _) = parent_child(&mut self.cos, parent, index, n);
+                let (psin, _) = parent_child(&mut self.sin, parent, index, n);
+                let p = Parent {
+                    x: px,
+                    y: py,
+                    scale_x: pscale_x,
+                    scale_y: pscale_y,
+                    cos: pcos,
+                    sin: psin,
+                };
+                transform(&p, x, y);
+                if bone.inherit_scale {
+                    for j in 0..n {
+                        scale_x[j] *= p.scale_x[j];
+                        scale_y[j] *= p.scale_y[j];
+                    }
+                }
+                for j in 0..n {
+                    // a reflection reverses the direction of local rotations
+                    if p.scale_x[j] * p.scale_y[j] < 0.0 {
+                        rotation[j] = -rotation[j];
+                    }
+                    if bone.inherit_rotation {
+                        rotation[j] += protation[j];
+                    }
+                }
+            }
+            None => {
+                let (position, scale) = root;
+                for j in range.clone() {
+                    self.x[j] = self.x[j] * scale[0] + position[0];
+                    self.y[j] = self.y[j] * scale[1] + position[1];
+                    self.scale_x[j] *= scale[0];
+                    self.scale_y[j] *= scale[1];
+                    if scale[0] * scale[1] < 0.0 {
+                        self.rotation[j] = -self.rotation[j];
+                    }
+                }
+            }
+        }
+
+        for j in range {
+            self.cos[j] = self.rotation[j].cos();
+            self.sin[j] = self.rotation[j].sin();
+        }
+    }
+}
+
+fn fill(values: &mut [f32], value: f32) {
+    for v in values {
+        *v = value;
+    }
+}
+
+/// values of the parent and of the child bone, parents being before their children
+fn parent_child(values: &mut [f32], parent: usize, child: usize, n: usize) -> (&[f32], &mut [f32]) {
+    let (before, after) = values.split_at_mut(child * n);
+    (&before[parent * n..(parent + 1) * n], &mut after[..n])
+}
+
+/// transforms positions by the parent srts, as `SRT::transform` does, 4 instances at a time
+#[cfg(target_arch = "x86_64")]
+fn transform(p: &Parent, x: &mut [f32], y: &mut [f32]) {
+    use std::arch::x86_64::*;
+
+    let n = x.len();
+    assert!(
+        y.len() == n
+            && [p.x, p.y, p.scale_x, p.scale_y, p.cos, p.sin]
+                .iter()
+                .all(|v| v.len() == n)
+    );
+    let lanes = n - n % 4;
+    // SSE is part of the x86_64 baseline, all slices have at least `lanes` values
+    unsafe {
+        for j in (0..lanes).step_by(4) {
+            let load = |v: &[f32]| _mm_loadu_ps(v.as_ptr().add(j));
+            let (cos, sin) = (load(p.cos), load(p.sin));
+            let (scale_x, scale_y) = (load(p.scale_x), load(p.scale_y));
+            let (vx, vy) = (load(x), load(y));
+            let wx = _mm_add_ps(
+                _mm_sub_ps(
+                    _mm_mul_ps(_mm_mul_ps(cos, vx), scale_x),
+                    _mm_mul_ps(_mm_mul_ps(sin, vy), scale_y),
+                ),
+                load(p.x),
+            );
+            let wy = _mm_add_ps(
+                _mm_add_ps(
+                    _mm_mul_ps(_mm_mul_ps(sin, vx), scale_x),
+                    _mm_mul_ps(_mm_mul_ps(cos, vy), scale_y),
+                ),
+                load(p.y),
+            );
+            _mm_storeu_ps(x.as_mut_ptr().add(j), wx);
+            _mm_storeu_ps(y.as_mut_ptr().add(j), wy);
+        }
+    }
+    transform_scalar(p, lanes, x, y);
+}
+
+/// transforms positions by the parent srts, as `SRT::transform` does
+#[cfg(not(target_arch = "x86_64"))]
+fn transform(p: &Parent, x: &mut [f32], y: &mut [f32]) {
+    transform_scalar(p, 0, x, y);
+}
+
+/// transforms positions from `start` one instance at a time
+fn transform_scalar(p: &Parent, start: usize, x: &mut [f32], y: &mut [f32]) {
+    for j in start..x.len() {
+        let (vx, vy) = (x[j], y[j]);
+        x[j] = p.cos[j] * vx * p.scale_x[j] - p.sin[j] * vy * p.scale_y[j] + p.x[j];
+        y[j] = p.sin[j] * vx * p.scale_x[j] + p.cos[j] * vy * p.scale_y[j] + p.y[j];
+    }
+}
@@ -0,0 +1,14 @@
+//! Module to describe the geometry of a pose, for debug overlays
+
+/// World space geometry of a pose
+#[derive(Debug, Clone, Default)]
+pub struct DebugGeometry {
+    /// segments from the origin to the end of each active bone
+    pub bones: Vec<([f32; 2], [f32; 2])>,
+    /// anchor of each slot with an attachment: center of regions, bone origin otherwise
+    pub slots: Vec<[f32; 2]>,
+    /// hull of each mesh
+    pub hulls: Vec<Vec<[f32; 2]>>,
+    /// outline of each bounding box
+    pub bounding_boxes: Vec<Vec<[f32; 2]>>,
+}
@@ -1,16 +1,22 @@
 //! Module to interpolate animated sprites
 
+pub mod bake;
+pub mod batch;
+pub mod debug;
 pub mod iter;
+pub mod pose;
+pub mod render;
 pub mod skin;
 pub mod sprite;
+pub mod svg;
 
-use super::timeline::{BoneTimeline, SlotTimeline};
+use super::handle::{BoneHandle, SlotHandle};
+use super::timeline::{BoneTimeline, CurveEvaluation, SlotTimeline};
 use super::util;
-use super::Bone;
 use super::SkeletonError;
-use super::Slot;
 use json;
 use skeleton::attachment::Attachment;
+use skeleton::slot::Slot;
 use std::collections::HashMap;
 
 /// Wrapper on attachment depending whether slot attachment is animated or not
@@ -23,11 +29,48 @@ pub enum AttachmentWrapper<'a> {
     ),
 }
 
+impl<'a> AttachmentWrapper<'a> {
+    /// attachment of the slot at `time`, with its name if it is animated
+    pub fn get(
+        &self,
+        anim: Option<&'a SlotTimeline>,
+        time: f32,
+    ) -> (Option<&'a str>, Option<&'a Attachment>) {
+        match *self {
+            AttachmentWrapper::Static(attach) => (None, attach),
+            AttachmentWrapper::Dynamic(attach, ref names) => {
+                match anim.and_then(|anim| anim.interpolate_attachment(time)) {
+                    Some(Some(name)) => (Some(name), names.get(name).cloned().unwrap_or(None)),
+                    Some(None) | None => (None, attach),
+                }
+            }
+        }
+    }
+
+    /// attachment of `slot` at `time` with its name, empty for unnamed attachments built at
+    /// runtime, `None` if there is no attachment
+    pub fn get_named(
+        &self,
+        slot: &'a Slot,
+        anim: Option<&'a SlotTimeline>,
+        time: f32,
+    ) -> Option<(&'a str, &'a Attachment)> {
+        let (name, attachment) = self.get(anim, time);
+        let attachment = attachment?;
+        let name = name
+            .or_else(|| attachment.name().or(slot.attachment.as_ref()).map(|n| &**n))
+            .unwrap_or("");
+        Some((name, attachment))
+    }
+}
+
 /// Animation with precomputed data
 pub struct Animation {
+    pub(crate) name: String,
     bones: Vec<(usize, BoneTimeline)>,
     slots: Vec<(usize, SlotTimeline)>,
     events: Vec<json::EventKeyframe>,
+    #[allow(dead_code)]
     draworder: Vec<json::DrawOrderTimeline>,
     duration: f32,
 }
@@ -35,77 +78,79 @@ pub struct Animation {
 impl Animation {
     /// Creates a from_json Animation
     pub fn from_json(
+        name: String,
         animation: json::Animation,
-        bones: &[Bone],
-        slots: &[Slot],
+        bones: &util::Names,
+        slots: &util::Names,
+        scale: f32,
+        curves: CurveEvaluation,
     ) -> Result<Animation, SkeletonError> {
-        let duration = Animation::duration(&animation);
-
         let mut abones = Vec::new();
         for jbones in animation.bones.into_iter() {
-            for (name, timelines) in jbones.into_iter() {
-                let index = util::bone_index(&name, bones)?;
-                let timeline = BoneTimeline::from_json(timelines)?;
+            for (bone, timelines) in jbones.into_iter() {
+                let at = |e: SkeletonError| e.at(&format!("bones.{}", bone));
+                let index = util::bone_index(&bone, bones).map_err(at)?;
+                let timeline = BoneTimeline::from_json(timelines, scale, curves).map_err(at)?;
                 abones.push((index, timeline));
             }
         }
 
         let mut aslots = Vec::new();
         for jslots in animation.slots.into_iter() {
-            for (name, timelines) in jslots.into_iter() {
-                let index = util::slot_index(&name, slots)?;
-                let timeline = SlotTimeline::from_json(timelines)?;
+            for (slot, timelines) in jslots.into_iter() {
+                let at = |e: SkeletonError| e.at(&format!("slots.{}", slot));
+                let index = util::slot_index(&slot, slots).map_err(at)?;
+                let timeline = SlotTimeline::from_json(timelines, curves).map_err(at)?;
                 aslots.push((index, timeline));
             }
         }
 
+        // json timelines are maps: keeps them in bone and slot order
+        abones.sort_by_key(|&(i, _)| i);
+        aslots.sort_by_key(|&(i, _)| i);
+
+        let duration = abones
+            .iter()
+            .map(|(_, t)| t.duration())
+            .chain(aslots.iter().map(|(_, t)| t.duration()))
+            .fold(0f32, f32::max);
+
         Ok(Animation {
+            name,
             duration,
             bones: abones,
             slots: aslots,
-            events: animation.events.unwrap_or(Vec::new()),
-            draworder: animation.draworder.unwrap_or(Vec::new()),
+            events: animation.events.unwrap_or_default(),
+            draworder: animation.draworder.unwrap_or_default(),
         })
     }
 
-    pub fn duration(animation: &json::Animation) -> f32 {
-        animation
-            .bones
-            .iter()
-            .flat_map(|bones| {
-                bones.values().flat_map(|timelines| {
-                    timelines
-                        .translate
-                        .iter()
-                        .flat_map(|translate| translate.iter().map(|e| e.time))
-                        .chain(
-                            timelines
-                                .rotate
-                                .iter()
-                                .flat_map(|rotate| rotate.iter().map(|e| e.time)),
-                        )
-                        .chain(
-                            timelines
-                                .scale
-                                .iter()
-                                .flat_map(|scale| scale.iter().map(|e| e.time)),
-                        )
-                })
-            })
-            .chain(animation.slots.iter().flat_map(|slots| {
-                slots.values().flat_map(|timelines| {
-                    timelines
-                        .attachment
-                        .iter()
-                        .flat_map(|attachment| attachment.iter().map(|e| e.time))
-                        .chain(
-                            timelines
-                                .color
-                                .iter()
-                                .flat_map(|color| color.iter().map(|e| e.time)),
-                        )
-                })
-            }))
-            .fold(0.0f32, f32::max)
+    /// name of the animation
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// time of the last key of all timelines
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Iterates over the animated bones and their timelines, in bone order
+    pub fn bone_timelines<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (BoneHandle, &'a BoneTimeline)> + 'a {
+        self.bones.iter().map(|(i, t)| (BoneHandle(*i), t))
+    }
+
+    /// Iterates over the animated slots and their timelines, in slot order
+    pub fn slot_timelines<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (SlotHandle, &'a SlotTimeline)> + 'a {
+        self.slots.iter().map(|(i, t)| (SlotHandle(*i), t))
+    }
+
+    /// number of events keys
+    pub fn events_count(&self) -> usize {
+        self.events.len()
     }
 }
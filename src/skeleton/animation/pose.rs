@@ -0,0 +1,102 @@
+//! Module to sample animations into a reusable buffer, without allocating on every frame
+
+use super::sprite::{self, SlotIter};
+use skeleton::timeline::{BoneTimeline, Cursor};
+use skeleton::{attachment::Attachment, color::Color, slot::BlendMode, srt::SRT};
+
+/// Bones world srts sampled at a given time, reused from frame to frame
+///
+/// ```no_run
+/// # let skeleton: spine::skeleton::Skeleton = unsafe { std::mem::uninitialized() };
+/// use spine::skeleton::animation::pose::Pose;
+///
+/// let animation = skeleton.get_animated_skin("default", Some("walk")).unwrap();
+/// let mut pose = Pose::new();
+/// for frame in 0..100 {
+///     animation.sample(frame as f32 / 30.0, &mut pose);
+///     for sprite in animation.sprites(&pose) {
+///         // draw sprite.attachment with sprite.srt and sprite.slot_srt
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Pose {
+    pub(crate) srts: Vec<SRT>,
+    pub(crate) time: f32,
+    /// timelines cursors of each bone, speeding up playback moving forward
+    pub(crate) cursors: Vec<[Cursor; BoneTimeline::CURSORS]>,
+}
+
+impl Pose {
+    /// creates an empty pose, its buffer grows on the first sampling only
+    pub fn new() -> Pose {
+        Pose::default()
+    }
+
+    /// time of the last sampling
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// world srts of all skeleton bones, indexed by bone
+    pub fn srts(&self) -> &[SRT] {
+        &self.srts
+    }
+}
+
+/// Interpolated slot borrowing its transformations from the pose and the attachment
+#[derive(Debug)]
+pub struct PoseSprite<'a> {
+    /// attachment name
+    pub attachment: &'a str,
+    /// color, skeleton, slot and attachment colors multiplied together
+    pub color: Color,
+    /// dark color, if the slot uses two color tinting
+    pub dark_color: Option<Color>,
+    /// blend mode of the slot
+    pub blend_mode: BlendMode,
+    /// world srt of the slot bone
+    pub srt: &'a SRT,
+    /// local srt on slot (identity for meshes, see `MeshAttachment::world_vertices`)
+    pub slot_srt: &'a SRT,
+    /// index of the slot bone
+    pub(crate) bone_index: usize,
+    /// attachment geometry
+    pub(crate) data: &'a Attachment,
+}
+
+impl<'a> PoseSprite<'a> {
+    /// color with rgb channels multiplied by alpha, for premultiplied alpha blending
+    pub fn premultiplied_color(&self) -> Color {
+        self.color.premultiply()
+    }
+
+    pub fn to_matrix3(&self) -> [[f32; 3]; 3] {
+        sprite::matrix3(self.srt, self.slot_srt)
+    }
+}
+
+/// Iterator over the sprites of a pose
+pub struct PoseSprites<'a> {
+    pub(crate) iter: SlotIter<'a>,
+    pub(crate) pose: &'a Pose,
+    /// skeleton color
+    pub(crate) color: Color,
+}
+
+impl<'a> Iterator for PoseSprites<'a> {
+    type Item = PoseSprite<'a>;
+    fn next(&mut self) -> Option<PoseSprite<'a>> {
+        let pose = self.pose;
+        sprite::next_slot(&mut self.iter, pose.time, self.color).map(|slot| PoseSprite {
+            attachment: slot.attachment,
+            srt: &pose.srts[slot.bone_index],
+            slot_srt: slot.slot_srt,
+            color: slot.color,
+            dark_color: slot.dark_color,
+            blend_mode: slot.blend_mode,
+            bone_index: slot.bone_index,
+            data: slot.data,
+        })
+    }
+}
@@ -0,0 +1,326 @@
+//! Module to draw a pose on the CPU, e.g. for thumbnails or golden image tests
+//!
+//! Region and mesh attachments are rasterized as textured triangles sampled from atlas pages
+//! (bilinear filtering, clamped to the page edges), tinted by the sprite colors and blended
+//! with the slot blend mode. Pixels are accumulated with premultiplied alpha.
+
+use super::pose::{Pose, PoseSprite};
+use super::skin::SkinAnimation;
+use skeleton::attachment::Attachment;
+use skeleton::color::Color;
+use skeleton::slot::BlendMode;
+
+/// Atlas page pixels, RGBA with 8 bits per channel, rows from top to bottom
+#[derive(Debug, Clone, Copy)]
+pub struct Image<'a> {
+    /// width in pixels
+    pub width: usize,
+    /// height in pixels
+    pub height: usize,
+    /// `width * height * 4` bytes, missing pixels are transparent
+    pub pixels: &'a [u8],
+    /// color channels are already multiplied by alpha (`pma: true` atlases)
+    pub premultiplied: bool,
+}
+
+impl<'a> Image<'a> {
+    /// creates an image with straight alpha
+    pub fn new(width: usize, height: usize, pixels: &'a [u8]) -> Image<'a> {
+        Image {
+            width,
+            height,
+            pixels,
+            premultiplied: false,
+        }
+    }
+
+    /// premultiplied texel, clamped to the image edges
+    fn texel(&self, x: isize, y: isize) -> [f32; 4] {
+        let clamp = |v: isize, len: usize| v.max(0).min(len as isize - 1) as usize;
+        let i = (clamp(y, self.height) * self.width + clamp(x, self.width)) * 4;
+        match self.pixels.get(i..i + 4) {
+            Some(p) => {
+                let a = f32::from(p[3]) / 255.0;
+                let alpha = if self.premultiplied { 1.0 } else { a };
+                let c = |c: u8| f32::from(c) / 255.0 * alpha;
+                [c(p[0]), c(p[1]), c(p[2]), a]
+            }
+            None => [0.0; 4],
+        }
+    }
+
+    /// premultiplied color at texture coordinates (u, v), filtered bilinearly
+    fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let (a, b) = (self.texel(x0, y0), self.texel(x0 + 1, y0));
+        let (c, d) = (self.texel(x0, y0 + 1), self.texel(x0 + 1, y0 + 1));
+        let mut texel = [0.0; 4];
+        for i in 0..4 {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            texel[i] = top + (bottom - top) * fy;
+        }
+        texel
+    }
+}
+
+/// RGBA render target
+///
+/// ```no_run
+/// # let skeleton: spine::skeleton::Skeleton = unsafe { std::mem::uninitialized() };
+/// # let page: Vec<u8> = Vec::new();
+/// use spine::skeleton::animation::pose::Pose;
+/// use spine::skeleton::animation::render::{Framebuffer, Image};
+///
+/// let animation = skeleton.get_animated_skin("default", Some("walk")).unwrap();
+/// let mut pose = Pose::new();
+/// animation.sample(0.5, &mut pose);
+/// // skeleton origin at the bottom center of a 256x256 thumbnail, at half size
+/// let mut target = Framebuffer::new(256, 256).with_view([128.0, 240.0], 0.5);
+/// target.draw(&animation, &pose, &[Image::new(1024, 1024, &page)]);
+/// let rgba = target.to_rgba8();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    /// premultiplied colors, rows from top to bottom
+    pixels: Vec<[f32; 4]>,
+    /// pixel coordinates of the skeleton origin
+    origin: [f32; 2],
+    /// pixels per skeleton unit
+    scale: f32,
+}
+
+impl Framebuffer {
+    /// creates a transparent framebuffer, the skeleton origin at its center
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width * height],
+            origin: [width as f32 * 0.5, height as f32 * 0.5],
+            scale: 1.0,
+        }
+    }
+
+    /// Places the skeleton origin at pixel coordinates `origin` (from the top-left corner)
+    /// and draws `scale` pixels per skeleton unit, y axis going up
+    pub fn with_view(mut self, origin: [f32; 2], scale: f32) -> Framebuffer {
+        self.origin = origin;
+        self.scale = scale;
+        self
+    }
+
+    /// width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// fills the framebuffer with a color
+    pub fn clear(&mut self, color: Color) {
+        let c = color.premultiply();
+        for p in &mut self.pixels {
+            *p = [c.r, c.g, c.b, c.a];
+        }
+    }
+
+    /// color of a pixel, `None` outside of the framebuffer
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width {
+            return None;
+        }
+        self.pixels.get(y * self.width + x).map(|p| {
+            if p[3] > 0.0 {
+                Color::new(p[0] / p[3], p[1] / p[3], p[2] / p[3], p[3])
+            } else {
+                Color::new(0.0, 0.0, 0.0, 0.0)
+            }
+        })
+    }
+
+    /// pixels as RGBA bytes with straight alpha, rows from top to bottom
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut rgba = Vec::with_capacity(self.pixels.len() * 4);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                rgba.extend_from_slice(&self.pixel(x, y).unwrap_or_default().to_rgba8());
+            }
+        }
+        rgba
+    }
+
+    /// draws the sprites of a pose sampled by `animation`
+    ///
+    /// Attachments are drawn from `pages[texture.page]`, or from the first page if they have
+    /// no texture region. Bounding boxes and attachments without page are not drawn, nor is a
+    /// pose not sampled by `animation` (see `SkinAnimation::sprites`).
+    pub fn draw(&mut self, animation: &SkinAnimation, pose: &Pose, pages: &[Image]) {
+        for sprite in animation.sprites(pose) {
+            self.draw_sprite(&sprite, pose, pages);
+        }
+    }
+
+    /// draws a single sprite of `pose`
+    fn draw_sprite(&mut self, sprite: &PoseSprite, pose: &Pose, pages: &[Image]) {
+        let (vertices, uvs, triangles, texture) = match *sprite.data {
+            Attachment::Region(ref region) => {
+                let corners = [[-1.0, -1.0], [-1.0, 1.0], [1.0, 1.0], [1.0, -1.0]];
+                let vertices = corners
+                    .iter()
+                    .map(|&c| sprite.srt.transform(region.srt.transform(c)))
+                    .collect();
+                let triangles: &[u16] = &[0, 1, 2, 2, 3, 0];
+                (vertices, &region.uvs[..], triangles, &region.texture)
+            }
+            Attachment::Mesh(ref mesh) => {
+                let vertices = mesh.world_vertices(pose.srts(), sprite.bone_index);
+                (vertices, &mesh.uvs[..], &mesh.triangles[..], &mesh.texture)
+            }
+            Attachment::BoundingBox(_) => return,
+        };
+        let page = match pages.get(texture.as_ref().map_or(0, |t| t.page)) {
+            Some(page) => page,
+            None => return,
+        };
+
+        let tint = Tint {
+            light: sprite.color,
+            dark: sprite.dark_color,
+            blend_mode: sprite.blend_mode,
+        };
+        for triangle in triangles.chunks(3).filter(|t| t.len() == 3) {
+            let corners = [
+                triangle[0] as usize,
+                triangle[1] as usize,
+                triangle[2] as usize,
+            ];
+            if corners
+                .iter()
+                .any(|&i| i >= vertices.len() || 2 * i + 1 >= uvs.len())
+            {
+                continue;
+            }
+            let points = [0, 1, 2].map(|k| {
+                let [x, y] = vertices[corners[k]];
+                [
+                    self.origin[0] + x * self.scale,
+                    self.origin[1] - y * self.scale,
+                ]
+            });
+            let uvs = [0, 1, 2].map(|k| [uvs[2 * corners[k]], uvs[2 * corners[k] + 1]]);
+            self.fill_triangle(points, uvs, page, &tint);
+        }
+    }
+
+    /// rasterizes a textured triangle, pixels being covered if their center is inside
+    ///
+    /// Pixels on an edge shared by two triangles are drawn by one of them only
+    fn fill_triangle(
+        &mut self,
+        mut p: [[f32; 2]; 3],
+        mut uvs: [[f32; 2]; 3],
+        page: &Image,
+        tint: &Tint,
+    ) {
+        let edge = |a: [f32; 2], b: [f32; 2], c: [f32; 2]| {
+            (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+        };
+        let mut area = edge(p[0], p[1], p[2]);
+        if !area.is_finite() || area == 0.0 {
+            return;
+        }
+        if area < 0.0 {
+            p.swap(1, 2);
+            uvs.swap(1, 2);
+            area = -area;
+        }
+
+        // pixels whose center is in the bounding box of the triangle
+        let min = |i: usize| p.iter().map(|v| v[i]).fold(f32::INFINITY, f32::min);
+        let max = |i: usize| p.iter().map(|v| v[i]).fold(f32::NEG_INFINITY, f32::max);
+        let range = |min: f32, max: f32, len: usize| {
+            let start = (min - 0.5).ceil().max(0.0) as usize;
+            let end = ((max - 0.5).floor() + 1.0).max(0.0).min(len as f32) as usize;
+            start..end
+        };
+        // edges opposite to each vertex, owning their pixels if they go down or left
+        let edges = [(p[1], p[2]), (p[2], p[0]), (p[0], p[1])];
+        let owns = edges.map(|(a, b)| b[1] > a[1] || (b[1] == a[1] && b[0] < a[0]));
+
+        for y in range(min(1), max(1), self.height) {
+            for x in range(min(0), max(0), self.width) {
+                let center = [x as f32 + 0.5, y as f32 + 0.5];
+                let w = edges.map(|(a, b)| edge(a, b, center));
+                if (0..3).any(|i| w[i] < 0.0 || (w[i] == 0.0 && !owns[i])) {
+                    continue;
+                }
+                let (u, v) = (0..3).fold((0.0, 0.0), |(u, v), i| {
+                    (u + uvs[i][0] * w[i] / area, v + uvs[i][1] * w[i] / area)
+                });
+                let color = tint.apply(page.sample(u, v));
+                let pixel = &mut self.pixels[y * self.width + x];
+                *pixel = tint.blend(color, *pixel);
+            }
+        }
+    }
+}
+
+/// Colors and blend mode of a sprite
+struct Tint {
+    light: Color,
+    dark: Option<Color>,
+    blend_mode: BlendMode,
+}
+
+impl Tint {
+    /// tints a premultiplied texel, as the two color tinting shaders of the official runtimes
+    fn apply(&self, t: [f32; 4]) -> [f32; 4] {
+        let (light, alpha) = ([self.light.r, self.light.g, self.light.b], self.light.a);
+        let mut color = [0.0, 0.0, 0.0, t[3] * alpha];
+        for i in 0..3 {
+            color[i] = match self.dark {
+                // dark color where the texture is dark, light color where it is light
+                Some(dark) => {
+                    let dark = [dark.r, dark.g, dark.b][i];
+                    ((t[3] - t[i]) * dark + t[i] * light[i]) * alpha
+                }
+                None => t[i] * light[i] * alpha,
+            };
+        }
+        color
+    }
+
+    /// blends a premultiplied color over a premultiplied destination
+    ///
+    /// Alpha is always blended as with `BlendMode::Normal`
+    fn blend(&self, s: [f32; 4], d: [f32; 4]) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        for i in 0..3 {
+            out[i] = match self.blend_mode {
+                BlendMode::Normal => s[i] + d[i] * (1.0 - s[3]),
+                BlendMode::Additive => s[i] + d[i],
+                BlendMode::Multiply => s[i] * d[i] + d[i] * (1.0 - s[3]),
+                BlendMode::Screen => s[i] + d[i] * (1.0 - s[i]),
+            };
+        }
+        out[3] = s[3] + d[3] * (1.0 - s[3]);
+        // additive colors may exceed the opacity
+        for i in 0..3 {
+            out[i] = out[i].min(out[3]).max(0.0);
+        }
+        out
+    }
+}
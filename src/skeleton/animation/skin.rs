@@ -1,14 +1,24 @@
+use super::bake::{BakedAnimation, BoneTrack, SlotTrack};
+use super::batch::BatchPose;
+use super::debug::DebugGeometry;
 use super::iter::AnimationIter;
-use super::sprite::Sprites;
+use super::pose::{Pose, PoseSprites};
+use super::sprite::{self, Sprites};
+use super::svg::{SvgOptions, SvgWriter};
 use super::AttachmentWrapper;
-use skeleton::timeline::{BoneTimeline, SlotTimeline};
-use skeleton::{bone::Bone, error::SkeletonError, slot::Slot, srt::SRT, Skeleton};
+use skeleton::attachment::Attachment;
+use skeleton::reduce::Tolerance;
+use skeleton::timeline::{BoneTimeline, Cursor, SlotTimeline};
+use skeleton::{bone::Bone, color::Color, error::SkeletonError, slot::Slot, srt::SRT, Skeleton};
 
 /// Struct to handle animated skin and calculate sprites
 pub struct SkinAnimation<'a> {
-    anim_bones: Vec<(&'a Bone, Option<&'a BoneTimeline>)>,
+    anim_bones: Vec<(&'a Bone, bool, Option<&'a BoneTimeline>)>,
     anim_slots: Vec<(&'a Slot, AttachmentWrapper<'a>, Option<&'a SlotTimeline>)>,
     duration: f32,
+    position: [f32; 2],
+    scale: [f32; 2],
+    color: Color,
 }
 
 impl<'a> SkinAnimation<'a> {
@@ -25,30 +35,53 @@ impl<'a> SkinAnimation<'a> {
         // get animation
         let (animation, duration) = if let Some(animation) = animation {
             let anim = skeleton
-                .animations
-                .get(animation)
+                .find_animation(animation)
+                .map(|handle| &skeleton.animations[handle.index()])
                 .ok_or_else(|| SkeletonError::AnimationNotFound(animation.to_owned()))?;
             (Some(anim), anim.duration)
         } else {
             (None, 0f32)
         };
 
+        // skin-required bones are only active if the skin (or one of their children) requires
+        // them, bones of an inactive parent are inactive
+        let mut active = skeleton
+            .bones
+            .iter()
+            .map(|b| !b.skin_required)
+            .collect::<Vec<_>>();
+        for &i in skin.bones.iter().chain(default_skin.bones.iter()) {
+            let mut bone = Some(i);
+            while let Some(i) = bone {
+                active[i] = true;
+                bone = skeleton.bones[i].parent_index;
+            }
+        }
+        for (i, bone) in skeleton.bones.iter().enumerate() {
+            if let Some(parent) = bone.parent_index {
+                active[i] = active[i] && active[parent];
+            }
+        }
+
+        // timelines by bone and slot index
+        let mut bone_timelines = vec![None; skeleton.bones.len()];
+        let mut slot_timelines = vec![None; skeleton.slots.len()];
+        if let Some(anim) = animation {
+            for (i, timeline) in &anim.bones {
+                bone_timelines[*i] = Some(timeline);
+            }
+            for (i, timeline) in &anim.slots {
+                slot_timelines[*i] = Some(timeline);
+            }
+        }
+
         // get bone related data
         let anim_bones = skeleton
             .bones
             .iter()
+            .zip(bone_timelines)
             .enumerate()
-            .map(|(i, b)| {
-                (
-                    b,
-                    animation.and_then(|anim| {
-                        anim.bones
-                            .iter()
-                            .find(|&&(idx, _)| idx == i)
-                            .map(|&(_, ref a)| a)
-                    }),
-                )
-            })
+            .map(|(i, (b, anim))| (b, active[i], anim))
             .collect();
 
         let find_attach =
@@ -59,15 +92,11 @@ impl<'a> SkinAnimation<'a> {
             .slots
             .iter()
             .enumerate()
+            .filter(|&(_, s)| active[s.bone_index])
             .map(|(i, s)| {
-                let anim = animation.and_then(|anim| {
-                    anim.slots
-                        .iter()
-                        .find(|&&(idx, _)| idx == i)
-                        .map(|&(_, ref anim)| anim)
-                });
-
-                let slot_attach = s.attachment.as_ref().and_then(|name| find_attach(i, &name));
+                let anim = slot_timelines[i];
+
+                let slot_attach = s.attachment.as_ref().and_then(|name| find_attach(i, name));
                 let attach = match anim.map(|anim| anim.get_attachment_names()) {
                     Some(names) => {
                         if names.is_empty() {
@@ -88,6 +117,9 @@ impl<'a> SkinAnimation<'a> {
 
         Ok(SkinAnimation {
             duration,
+            position: [0.0, 0.0],
+            scale: [1.0, 1.0],
+            color: Color::WHITE,
             anim_bones,
             anim_slots,
         })
@@ -98,16 +130,63 @@ impl<'a> SkinAnimation<'a> {
         self.duration
     }
 
+    /// Sets the position of the skeleton root
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+
+    /// Sets the scale of the skeleton root, negative values flip the skeleton
+    ///
+    /// Flipping is exact as long as both scales have the same absolute value
+    pub fn set_scale(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale = [scale_x, scale_y];
+    }
+
+    /// Sets the skeleton color, multiplied with slots colors
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Gets the skeleton color
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+
     /// gets all bones srts at given time
     fn get_bones_srts(&self, time: f32) -> Vec<SRT> {
         let mut srts: Vec<SRT> = Vec::with_capacity(self.anim_bones.len());
-        for &(bone, anim) in &self.anim_bones {
+        self.compute_bones_srts(time, &mut srts, None);
+        srts
+    }
+
+    /// computes all bones srts at given time into `srts`, reusing its capacity
+    ///
+    /// `cursors` of each bone timelines start the search of keys if any
+    fn compute_bones_srts(
+        &self,
+        time: f32,
+        srts: &mut Vec<SRT>,
+        mut cursors: Option<&mut [[Cursor; BoneTimeline::CURSORS]]>,
+    ) {
+        srts.clear();
+        for (i, &(bone, active, anim)) in self.anim_bones.iter().enumerate() {
+            // inactive bones keep their setup pose, none of their slots is drawn
+            if !active {
+                srts.push(bone.srt.clone());
+                continue;
+            }
+
             // starts with setup pose
             let mut srt = bone.srt.clone();
-            let mut rotation = 0.0;
+            let mut rotation = srt.rotation;
 
             // add animation srt
-            if let Some(anim_srt) = anim.map(|anim| anim.srt(time)) {
+            let anim_srt = match (anim, cursors.as_mut()) {
+                (Some(anim), Some(cursors)) => Some(anim.srt_with_cursors(time, &mut cursors[i])),
+                (Some(anim), None) => Some(anim.srt(time)),
+                (None, _) => None,
+            };
+            if let Some(anim_srt) = anim_srt {
                 srt.position[0] += anim_srt.position[0];
                 srt.position[1] += anim_srt.position[1];
                 rotation += anim_srt.rotation;
@@ -115,27 +194,90 @@ impl<'a> SkinAnimation<'a> {
                 srt.scale[1] *= anim_srt.scale[1];
             }
 
-            // inherit world from parent srt
-            if let Some(ref parent_srt) = bone.parent_index.and_then(|p| srts.get(p)) {
-                srt.position = parent_srt.transform(srt.position);
-                if bone.inherit_rotation {
-                    rotation += parent_srt.rotation;
+            // inherit world from parent srt, or from the skeleton root
+            let (reflect, parent_rotation) = match bone.parent_index.and_then(|p| srts.get(p)) {
+                Some(parent_srt) => {
+                    srt.position = parent_srt.transform(srt.position);
+                    if bone.inherit_scale {
+                        srt.scale[0] *= parent_srt.scale[0];
+                        srt.scale[1] *= parent_srt.scale[1];
+                    }
+                    let reflect = parent_srt.scale[0] * parent_srt.scale[1] < 0.0;
+                    let parent_rotation = if bone.inherit_rotation {
+                        parent_srt.rotation
+                    } else {
+                        0.0
+                    };
+                    (reflect, parent_rotation)
                 }
-                if bone.inherit_scale {
-                    srt.scale[0] *= parent_srt.scale[0];
-                    srt.scale[1] *= parent_srt.scale[1];
+                None => {
+                    srt.position = [
+                        srt.position[0] * self.scale[0] + self.position[0],
+                        srt.position[1] * self.scale[1] + self.position[1],
+                    ];
+                    srt.scale[0] *= self.scale[0];
+                    srt.scale[1] *= self.scale[1];
+                    (self.scale[0] * self.scale[1] < 0.0, 0.0)
                 }
+            };
+
+            // a reflection reverses the direction of local rotations
+            if reflect {
+                rotation = -rotation;
             }
+            rotation += parent_rotation;
 
             // re-calculate sin/cos only if rotation has changed
-            if rotation != 0.0 {
-                srt.rotation += rotation;
+            if rotation != srt.rotation {
+                srt.rotation = rotation;
                 srt.cos = srt.rotation.cos();
                 srt.sin = srt.rotation.sin();
             }
             srts.push(srt)
         }
-        srts
+    }
+
+    /// Samples bones at given time into `pose`
+    ///
+    /// Unlike `interpolate`, nothing is allocated once `pose` has been used with this skeleton
+    /// and times after the duration keep the last keys. Keys are searched from the ones of
+    /// the previous sampling, which is faster when time moves forward.
+    pub fn sample(&self, time: f32, pose: &mut Pose) {
+        pose.cursors.resize(
+            self.anim_bones.len(),
+            [Cursor::new(); BoneTimeline::CURSORS],
+        );
+        self.compute_bones_srts(time, &mut pose.srts, Some(&mut pose.cursors));
+        pose.time = time;
+    }
+
+    /// Samples bones of several instances, one per time of `times`, into `batch`
+    ///
+    /// Gives the same srts as `sample` for each instance, evaluating one timeline at a time for
+    /// all instances, then the world transforms of one bone at a time for all instances.
+    /// Sorting `times` speeds up key searches
+    pub fn sample_batch(&self, times: &[f32], batch: &mut BatchPose) {
+        batch.reset(self.anim_bones.len(), times.len());
+        for (i, &(bone, active, anim)) in self.anim_bones.iter().enumerate() {
+            batch.sample_bone(i, bone, active, anim, times, (self.position, self.scale));
+        }
+    }
+
+    /// Iterates over the sprites of a pose sampled with `sample`
+    ///
+    /// Nothing is iterated if the pose was not sampled by an animation of this skeleton, e.g. a
+    /// new pose or a pose with another number of bones
+    pub fn sprites<'b>(&'b self, pose: &'b Pose) -> PoseSprites<'b> {
+        let slots = if pose.srts.len() == self.anim_bones.len() {
+            &self.anim_slots[..]
+        } else {
+            &[]
+        };
+        PoseSprites {
+            iter: slots.iter(),
+            pose,
+            color: self.color,
+        }
     }
 
     /// Interpolates animated slots at given time
@@ -146,13 +288,154 @@ impl<'a> SkinAnimation<'a> {
 
         let srts = self.get_bones_srts(time);
         let iter = self.anim_slots.iter();
-        Some(Sprites { iter, srts, time })
+        Some(Sprites {
+            iter,
+            srts,
+            time,
+            color: self.color,
+        })
+    }
+
+    /// Samples the animation `fps` times per second into tracks played back by linear
+    /// interpolation
+    ///
+    /// With a `tolerance`, samples recovered by interpolating the kept ones are dropped
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fps` is not a positive number or gives more frames than a `u32` can count
+    pub fn bake(&self, fps: f32, tolerance: Option<Tolerance>) -> BakedAnimation {
+        assert!(
+            fps.is_finite() && fps > 0.0,
+            "invalid bake rate: {} fps",
+            fps
+        );
+        let frames = (self.duration * fps).ceil();
+        assert!(
+            frames < u32::MAX as f32,
+            "too many frames to bake: {}",
+            frames
+        );
+        let frames = frames as u32;
+        let mut bones = vec![BoneTrack::default(); self.anim_bones.len()];
+        let mut slots = self
+            .anim_slots
+            .iter()
+            .map(|&(slot, _, _)| SlotTrack::new(&slot.name))
+            .collect::<Vec<_>>();
+
+        let mut pose = Pose::new();
+        for frame in 0..frames + 1 {
+            // the last frame may be after the duration, where timelines keep their last keys
+            let time = frame as f32 / fps;
+            self.sample(time, &mut pose);
+            for (track, srt) in bones.iter_mut().zip(pose.srts()) {
+                track.push(frame, srt);
+            }
+            for (track, &(slot, ref attach, anim)) in slots.iter_mut().zip(&self.anim_slots) {
+                let color = anim
+                    .and_then(|anim| anim.interpolate_color(time))
+                    .unwrap_or(slot.color);
+                let dark_color = anim
+                    .and_then(|anim| anim.interpolate_dark_color(time))
+                    .or(slot.dark_color);
+                let attachment = attach.get_named(slot, anim, time).map(|(name, _)| name);
+                track.push(frame, color, dark_color, attachment);
+            }
+        }
+
+        if let Some(ref tolerance) = tolerance {
+            for track in &mut bones {
+                track.reduce(tolerance);
+            }
+            for track in &mut slots {
+                track.reduce(tolerance);
+            }
+        }
+
+        BakedAnimation {
+            fps,
+            duration: self.duration,
+            bones,
+            slots,
+        }
+    }
+
+    /// Computes the world space geometry of the pose at given time, to draw the rig
+    pub fn debug(&self, time: f32) -> DebugGeometry {
+        let srts = self.get_bones_srts(time);
+        let mut debug = DebugGeometry::default();
+
+        for (&(bone, active, _), srt) in self.anim_bones.iter().zip(srts.iter()) {
+            if active {
+                debug
+                    .bones
+                    .push((srt.position, srt.transform([bone.length, 0.0])));
+            }
+        }
+
+        for &(slot, ref attach, anim) in &self.anim_slots {
+            let bone_srt = &srts[slot.bone_index];
+            match attach.get(anim, time).1 {
+                Some(Attachment::Region(region)) => {
+                    debug.slots.push(bone_srt.transform(region.srt.position));
+                }
+                Some(Attachment::Mesh(mesh)) => {
+                    debug.slots.push(bone_srt.position);
+                    debug.hulls.push(mesh.world_hull(&srts, slot.bone_index));
+                }
+                Some(Attachment::BoundingBox(bounding_box)) => {
+                    debug.slots.push(bone_srt.position);
+                    debug
+                        .bounding_boxes
+                        .push(bounding_box.world_vertices(&srts, slot.bone_index));
+                }
+                None => (),
+            }
+        }
+        debug
+    }
+
+    /// Exports the world space geometry of the pose at given time to an svg document
+    ///
+    /// Attachments are written in draw order, bones on top of them
+    pub fn to_svg(&self, time: f32, options: &SvgOptions) -> String {
+        let srts = self.get_bones_srts(time);
+        let mut svg = SvgWriter::new(options);
+
+        for &(slot, ref attach, anim) in &self.anim_slots {
+            let (name, attachment) = match attach.get_named(slot, anim, time) {
+                Some(named) => named,
+                None => continue,
+            };
+            match *attachment {
+                Attachment::Region(ref region) => {
+                    let matrix = sprite::matrix3(&srts[slot.bone_index], &region.srt);
+                    svg.region(&slot.name, name, matrix);
+                }
+                Attachment::Mesh(ref mesh) => {
+                    let vertices = mesh.world_vertices(&srts, slot.bone_index);
+                    svg.mesh(&slot.name, name, &vertices, &mesh.triangles);
+                }
+                Attachment::BoundingBox(ref bounding_box) => {
+                    let vertices = bounding_box.world_vertices(&srts, slot.bone_index);
+                    svg.bounding_box(&slot.name, name, &vertices);
+                }
+            }
+        }
+
+        for (&(bone, active, _), srt) in self.anim_bones.iter().zip(srts.iter()) {
+            if active {
+                svg.bone(&bone.name, srt.position, srt.transform([bone.length, 0.0]));
+            }
+        }
+        svg.finish()
     }
 
     /// Creates an iterator which iterates sprites at delta seconds interval
     pub fn run<'b: 'a>(&'b self, delta: f32) -> AnimationIter<'b> {
         AnimationIter {
-            skin_animation: &self,
+            skin_animation: self,
             time: 0f32,
             delta,
         }
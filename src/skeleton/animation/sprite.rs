@@ -1,97 +1,144 @@
 use super::AttachmentWrapper;
-use skeleton::{attachment::Attachment, slot::Slot, srt::SRT, timeline::SlotTimeline};
+use skeleton::{
+    attachment::Attachment,
+    color::Color,
+    slot::{BlendMode, Slot},
+    srt::SRT,
+    timeline::SlotTimeline,
+};
 use std::slice::Iter;
 
+/// Iterator over the slots of a `SkinAnimation`
+pub(crate) type SlotIter<'a> =
+    Iter<'a, (&'a Slot, AttachmentWrapper<'a>, Option<&'a SlotTimeline>)>;
+
 /// Interpolated slot with attachment and color
 #[derive(Debug)]
 pub struct Sprite<'a> {
     /// attachment name
     pub attachment: &'a str,
-    /// color
-    pub color: [u8; 4],
+    /// color, skeleton, slot and attachment colors multiplied together
+    pub color: Color,
+    /// dark color, if the slot uses two color tinting
+    pub dark_color: Option<Color>,
+    /// blend mode of the slot
+    pub blend_mode: BlendMode,
     /// srt
     pub srt: SRT,
-    /// local srt on slot
+    /// local srt on slot (identity for meshes, see `MeshAttachment::world_vertices`)
     pub slot_srt: SRT,
 }
 
 impl<'a> Sprite<'a> {
+    /// color with rgb channels multiplied by alpha, for premultiplied alpha blending
+    ///
+    /// The dark color is not affected
+    pub fn premultiplied_color(&self) -> Color {
+        self.color.premultiply()
+    }
+
     pub fn to_matrix3(&self) -> [[f32; 3]; 3] {
-        let mat1 = self.slot_srt.to_matrix3();
-        let mat2 = self.srt.to_matrix3();
-        [
-            [
-                mat2[0][0] * mat1[0][0] + mat2[1][0] * mat1[0][1],
-                mat2[0][1] * mat1[0][0] + mat2[1][1] * mat1[0][1],
-                0.0,
-            ],
-            [
-                mat2[1][0] * mat1[1][1] + mat2[0][0] * mat1[1][0],
-                mat2[1][1] * mat1[1][1] + mat2[0][1] * mat1[1][0],
-                0.0,
-            ],
-            [
-                mat2[2][0] + mat2[1][0] * mat1[2][1] + mat2[0][0] * mat1[2][0],
-                mat2[2][1] + mat2[1][1] * mat1[2][1] + mat2[0][1] * mat1[2][0],
-                1.0,
-            ],
-        ]
+        matrix3(&self.srt, &self.slot_srt)
     }
 }
 
+/// combines the bone srt and the local srt on slot into a 3x3 matrix
+pub(crate) fn matrix3(srt: &SRT, slot_srt: &SRT) -> [[f32; 3]; 3] {
+    let mat1 = slot_srt.to_matrix3();
+    let mat2 = srt.to_matrix3();
+    [
+        [
+            mat2[0][0] * mat1[0][0] + mat2[1][0] * mat1[0][1],
+            mat2[0][1] * mat1[0][0] + mat2[1][1] * mat1[0][1],
+            0.0,
+        ],
+        [
+            mat2[1][0] * mat1[1][1] + mat2[0][0] * mat1[1][0],
+            mat2[1][1] * mat1[1][1] + mat2[0][1] * mat1[1][0],
+            0.0,
+        ],
+        [
+            mat2[2][0] + mat2[1][0] * mat1[2][1] + mat2[0][0] * mat1[2][0],
+            mat2[2][1] + mat2[1][1] * mat1[2][1] + mat2[0][1] * mat1[2][0],
+            1.0,
+        ],
+    ]
+}
+
 /// Iterator over all sprites interpolated at a given time
 pub struct Sprites<'a> {
-    pub iter: Iter<'a, (&'a Slot, AttachmentWrapper<'a>, Option<&'a SlotTimeline>)>,
+    pub iter: SlotIter<'a>,
     pub srts: Vec<SRT>,
     pub time: f32,
+    /// skeleton color
+    pub color: Color,
 }
 
 impl<'a> Iterator for Sprites<'a> {
     type Item = Sprite<'a>;
     fn next<'b>(&'b mut self) -> Option<Sprite<'a>> {
-        while let Some(&(slot, ref skin_attach, anim)) = self.iter.next() {
-            // search animated attachment
-            let (name, skin_attach) = match skin_attach {
-                AttachmentWrapper::Static(ref attach) => (None, attach),
-                AttachmentWrapper::Dynamic(ref attach, ref names) => {
-                    match anim.unwrap().interpolate_attachment(self.time) {
-                        Some(Some(name)) => (Some(name), names.get(&*name).unwrap()),
-                        Some(None) | None => (None, attach),
-                    }
-                }
-            };
+        next_slot(&mut self.iter, self.time, self.color).map(|slot| Sprite {
+            attachment: slot.attachment,
+            srt: self.srts[slot.bone_index].clone(),
+            slot_srt: slot.slot_srt.clone(),
+            color: slot.color,
+            dark_color: slot.dark_color,
+            blend_mode: slot.blend_mode,
+        })
+    }
+}
 
-            // nothing to show if there is no attachment
-            if let Some(ref skin_attach) = *skin_attach {
-                // color
-                let color = anim
-                    .map(|anim| anim.interpolate_color(self.time))
-                    .unwrap_or_else(|| slot.color);
+/// Interpolated slot, before its bone world srt is known
+pub(crate) struct SlotSprite<'a> {
+    pub attachment: &'a str,
+    pub color: Color,
+    pub dark_color: Option<Color>,
+    pub blend_mode: BlendMode,
+    pub bone_index: usize,
+    pub slot_srt: &'a SRT,
+    /// attachment geometry
+    pub data: &'a Attachment,
+}
 
-                // attachment name
-                let attach_name = name
-                    .or_else(|| {
-                        skin_attach
-                            .name()
-                            .or_else(|| slot.attachment.as_ref())
-                            .map(|n| &**n)
-                    })
-                    .expect("no attachment name provided");
-                let slot_srt = match skin_attach {
-                    Attachment::Region(region) => region.srt.clone(),
-                    Attachment::Mesh(_) => unimplemented!("Mesh srt's are currently not supported"),
-                };
+/// Interpolates the next slot with a drawable attachment
+pub(crate) fn next_slot<'a>(
+    iter: &mut SlotIter<'a>,
+    time: f32,
+    skeleton_color: Color,
+) -> Option<SlotSprite<'a>> {
+    for &(slot, ref skin_attach, anim) in iter {
+        // search animated attachment, nothing to show if there is no attachment
+        if let Some((attachment, skin_attach)) = skin_attach.get_named(slot, anim, time) {
+            let slot_srt = match *skin_attach {
+                Attachment::Region(ref region) => &region.srt,
+                // mesh vertices are already relative to the bone
+                Attachment::Mesh(_) => &SRT::IDENTITY,
+                // bounding boxes are not drawn
+                Attachment::BoundingBox(_) => continue,
+            };
 
-                return Some(Sprite {
-                    attachment: attach_name,
-                    srt: self.srts[slot.bone_index].clone(),
-                    slot_srt,
-                    color,
-                });
-            }
-        }
+            // colors
+            let color = skeleton_color
+                * anim
+                    .and_then(|anim| anim.interpolate_color(time))
+                    .unwrap_or(slot.color)
+                * skin_attach.color();
+            let dark_color = anim
+                .and_then(|anim| anim.interpolate_dark_color(time))
+                .or(slot.dark_color);
 
-        // end of iter
-        None
+            return Some(SlotSprite {
+                attachment,
+                color,
+                dark_color,
+                blend_mode: slot.blend_mode,
+                bone_index: slot.bone_index,
+                slot_srt,
+                data: skin_attach,
+            });
+        }
     }
+
+    // end of iter
+    None
 }
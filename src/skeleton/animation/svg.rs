@@ -0,0 +1,273 @@
+//! Module to export the geometry of a pose to svg, for documentation or diffable visual tests
+//!
+//! Bones are drawn as segments, regions as quads, meshes as triangles and bounding boxes as
+//! polygons, in world space (y axis going up). No texture is decoded: images are referenced by
+//! attachment name and only drawn for regions. Elements with non finite coordinates (e.g.
+//! from a zero scale somewhere in the bone hierarchy) are skipped.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// What `SkinAnimation::to_svg` exports
+///
+/// ```no_run
+/// # let skeleton: spine::skeleton::Skeleton = unsafe { std::mem::uninitialized() };
+/// use spine::skeleton::animation::svg::SvgOptions;
+///
+/// let animation = skeleton.get_animated_skin("default", Some("walk")).unwrap();
+/// let options = SvgOptions::new()
+///     .bounding_boxes(false)
+///     .image("head", "images/head.png");
+/// let svg = animation.to_svg(0.5, &options);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    bones: bool,
+    regions: bool,
+    meshes: bool,
+    bounding_boxes: bool,
+    /// image reference of attachments, by attachment name
+    images: HashMap<String, String>,
+    margin: f32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            bones: true,
+            regions: true,
+            meshes: true,
+            bounding_boxes: true,
+            images: HashMap::new(),
+            margin: 10.0,
+        }
+    }
+}
+
+impl SvgOptions {
+    /// exports everything, without images
+    pub fn new() -> SvgOptions {
+        SvgOptions::default()
+    }
+
+    /// Exports bone segments if `true`
+    pub fn bones(mut self, bones: bool) -> SvgOptions {
+        self.bones = bones;
+        self
+    }
+
+    /// Exports region quads (and their images) if `true`
+    pub fn regions(mut self, regions: bool) -> SvgOptions {
+        self.regions = regions;
+        self
+    }
+
+    /// Exports mesh triangles if `true`
+    pub fn meshes(mut self, meshes: bool) -> SvgOptions {
+        self.meshes = meshes;
+        self
+    }
+
+    /// Exports bounding boxes if `true`
+    pub fn bounding_boxes(mut self, bounding_boxes: bool) -> SvgOptions {
+        self.bounding_boxes = bounding_boxes;
+        self
+    }
+
+    /// Draws the image at `href` in the quad of the region attachment named `attachment`
+    pub fn image(mut self, attachment: &str, href: &str) -> SvgOptions {
+        self.images.insert(attachment.to_owned(), href.to_owned());
+        self
+    }
+
+    /// Sets the space around the geometry, in skeleton units (10 by default)
+    pub fn margin(mut self, margin: f32) -> SvgOptions {
+        self.margin = margin;
+        self
+    }
+}
+
+/// Svg document being written, growing its bounds with the written geometry
+pub(crate) struct SvgWriter<'a> {
+    options: &'a SvgOptions,
+    body: String,
+    min: [f32; 2],
+    max: [f32; 2],
+}
+
+impl<'a> SvgWriter<'a> {
+    pub(crate) fn new(options: &'a SvgOptions) -> SvgWriter<'a> {
+        SvgWriter {
+            options,
+            body: String::new(),
+            min: [f32::INFINITY; 2],
+            max: [f32::NEG_INFINITY; 2],
+        }
+    }
+
+    /// segment from the origin to the end of a bone
+    pub(crate) fn bone(&mut self, name: &str, from: [f32; 2], to: [f32; 2]) {
+        if !self.options.bones || !finite(&[from, to]) {
+            return;
+        }
+        self.grow(&[from, to]);
+        let _ = writeln!(
+            self.body,
+            r#"<line class="bone" data-bone="{}" x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+            escape(name),
+            number(from[0]),
+            number(from[1]),
+            number(to[0]),
+            number(to[1])
+        );
+    }
+
+    /// quad of a region, `matrix` transforming the unit square (-1, -1) (1, 1) to world space
+    pub(crate) fn region(&mut self, slot: &str, attachment: &str, matrix: [[f32; 3]; 3]) {
+        if !self.options.regions || !finite(&matrix) {
+            return;
+        }
+        let corners = [[-1.0, -1.0], [-1.0, 1.0], [1.0, 1.0], [1.0, -1.0]].map(|[x, y]| {
+            [
+                matrix[0][0] * x + matrix[1][0] * y + matrix[2][0],
+                matrix[0][1] * x + matrix[1][1] * y + matrix[2][1],
+            ]
+        });
+        if let Some(href) = self.options.images.get(attachment) {
+            // images go down from their top-left corner
+            let m = [matrix[0][0], matrix[0][1], matrix[1][0], matrix[1][1]];
+            let _ = writeln!(
+                self.body,
+                r#"<image class="region" data-slot="{}" data-attachment="{}" href="{}" x="-1" y="-1" width="2" height="2" preserveAspectRatio="none" transform="matrix({} {} {} {} {} {}) scale(1 -1)"/>"#,
+                escape(slot),
+                escape(attachment),
+                escape(href),
+                number(m[0]),
+                number(m[1]),
+                number(m[2]),
+                number(m[3]),
+                number(matrix[2][0]),
+                number(matrix[2][1])
+            );
+        }
+        self.polygon("region", slot, attachment, &corners);
+    }
+
+    /// triangles of a mesh, `vertices` being in world space
+    pub(crate) fn mesh(
+        &mut self,
+        slot: &str,
+        attachment: &str,
+        vertices: &[[f32; 2]],
+        triangles: &[u16],
+    ) {
+        if !self.options.meshes {
+            return;
+        }
+        for triangle in triangles.chunks(3) {
+            let corners = triangle
+                .iter()
+                .filter_map(|&i| vertices.get(i as usize).cloned())
+                .collect::<Vec<_>>();
+            if corners.len() == 3 {
+                self.polygon("mesh", slot, attachment, &corners);
+            }
+        }
+    }
+
+    /// outline of a bounding box, `vertices` being in world space
+    pub(crate) fn bounding_box(&mut self, slot: &str, attachment: &str, vertices: &[[f32; 2]]) {
+        if self.options.bounding_boxes {
+            self.polygon("bounding-box", slot, attachment, vertices);
+        }
+    }
+
+    fn polygon(&mut self, class: &str, slot: &str, attachment: &str, points: &[[f32; 2]]) {
+        if !finite(points) {
+            return;
+        }
+        self.grow(points);
+        let points = points
+            .iter()
+            .map(|p| format!("{},{}", number(p[0]), number(p[1])))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            self.body,
+            r#"<polygon class="{}" data-slot="{}" data-attachment="{}" points="{}"/>"#,
+            class,
+            escape(slot),
+            escape(attachment),
+            points
+        );
+    }
+
+    fn grow(&mut self, points: &[[f32; 2]]) {
+        for p in points {
+            self.min = [self.min[0].min(p[0]), self.min[1].min(p[1])];
+            self.max = [self.max[0].max(p[0]), self.max[1].max(p[1])];
+        }
+    }
+
+    /// the whole document, its view box fitting the geometry and margin
+    pub(crate) fn finish(self) -> String {
+        let (min, max) = if self.min[0] <= self.max[0] {
+            (self.min, self.max)
+        } else {
+            ([0.0; 2], [0.0; 2])
+        };
+        let margin = self.options.margin;
+        let (width, height) = (
+            (max[0] - min[0] + 2.0 * margin).min(f32::MAX),
+            (max[1] - min[1] + 2.0 * margin).min(f32::MAX),
+        );
+        // y axis goes down in svg: the view box is flipped with the geometry
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">
+<style>
+line, polygon {{ fill: none; stroke-width: 1; vector-effect: non-scaling-stroke; }}
+.bone {{ stroke: #e04040; }}
+.region {{ stroke: #3080ff; }}
+.mesh {{ stroke: #ffa000; }}
+.bounding-box {{ stroke: #40c040; }}
+</style>
+<g transform="scale(1 -1)">
+"#,
+            number(min[0] - margin),
+            number(-max[1] - margin),
+            number(width),
+            number(height),
+            number(width),
+            number(height)
+        );
+        svg.push_str(&self.body);
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// number rounded to 3 decimals, without negative zeros, for stable diffs
+fn number(value: f32) -> f32 {
+    let rounded = (value * 1000.0).round() / 1000.0 + 0.0;
+    // huge values have no decimals, and would overflow
+    if rounded.is_finite() {
+        rounded
+    } else {
+        value
+    }
+}
+
+/// checks that all the coordinates can be written
+fn finite<T: AsRef<[f32]>>(points: &[T]) -> bool {
+    points
+        .iter()
+        .all(|p| p.as_ref().iter().all(|v| v.is_finite()))
+}
+
+/// escapes xml special characters of attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,59 @@
+use super::vertices;
+use json;
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
+use skeleton::srt::SRT;
+
+/// Polygon used for hit detection, not drawn
+#[derive(Debug, Clone)]
+pub struct BoundingBoxAttachment {
+    pub name: Option<String>,
+    /// color of the outline in the editor
+    pub color: Color,
+    /// vertices, (x, y) relative to the slot bone or (bind x, bind y, weight) if weighted
+    pub vertices: Vec<f32>,
+    /// bones of weighted vertices: bone count followed by bone indices, for each vertex
+    pub(crate) bones: Option<Vec<u32>>,
+}
+
+impl BoundingBoxAttachment {
+    pub fn new(
+        attachment: json::Attachment,
+        name: Option<String>,
+        scale: f32,
+    ) -> Result<BoundingBoxAttachment, SkeletonError> {
+        let mut vertices = attachment.vertices.unwrap_or_default();
+        let vertex_count = attachment
+            .vertex_count
+            .map_or(vertices.len() / 2, |c| c as usize);
+        let bones = if vertices.len() > vertex_count * 2 {
+            let (weights, bones) = vertices::split_weighted(&vertices)
+                .map_err(|e| SkeletonError::from(e).at("vertices"))?;
+            vertices = weights;
+            for v in vertices.chunks_mut(3) {
+                v[0] *= scale;
+                v[1] *= scale;
+            }
+            Some(bones)
+        } else {
+            for v in &mut vertices {
+                *v *= scale;
+            }
+            None
+        };
+
+        Ok(BoundingBoxAttachment {
+            name: attachment.name.or(name),
+            color: Color::from_rgba_hex(&attachment.color).map_err(|e| e.at("color"))?,
+            vertices,
+            bones,
+        })
+    }
+
+    /// outline in world space
+    ///
+    /// `srts` are the world srts of the skeleton bones
+    pub fn world_vertices(&self, srts: &[SRT], slot_bone: usize) -> Vec<[f32; 2]> {
+        vertices::world_vertices(&self.vertices, self.bones.as_deref(), srts, slot_bone)
+    }
+}
@@ -1,76 +1,141 @@
+use super::texture::TextureRegion;
+use super::vertices;
+use super::AttachmentError;
 use json;
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
+use skeleton::srt::SRT;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MeshAttachment {
     pub name: Option<String>,
+    /// name of the texture region to draw
+    pub path: Option<String>,
+    /// tint of the attachment, multiplied with the slot color
+    pub color: Color,
     pub triangles: Vec<u16>,
-    edges: Vec<i32>,
+    pub edges: Vec<i32>,
     pub vertices: Vec<f32>,
-    uvs: Vec<f32>,
-    bone_indices: Option<Vec<u32>>,
+    /// texture coordinates, relative to the texture region
+    pub region_uvs: Vec<f32>,
+    /// texture coordinates, relative to the texture page
+    pub uvs: Vec<f32>,
+    /// texture region, once resolved by an `AttachmentLoader`
+    pub texture: Option<TextureRegion>,
+    /// bones of weighted vertices: bone count followed by bone indices, for each vertex
+    pub(crate) bones: Option<Vec<u32>>,
+    /// number of vertices of the hull, the first ones
+    pub hull: usize,
     pub is_weighted: bool,
     pub world_vertices_length: u32,
 }
 
 impl MeshAttachment {
-    pub fn new(attachment: json::Attachment, name: Option<String>) -> MeshAttachment {
+    pub fn new(
+        attachment: json::Attachment,
+        name: Option<String>,
+        scale: f32,
+    ) -> Result<MeshAttachment, SkeletonError> {
+        let name = attachment.name.or(name);
+        let region_uvs = attachment.uvs.unwrap_or_default();
         let mut mesh = MeshAttachment {
-            name: attachment.name.or(name),
-            triangles: attachment.triangles.unwrap_or(Vec::new()),
-            edges: attachment.edges.unwrap_or(Vec::new()),
-            vertices: attachment.vertices.unwrap_or(Vec::new()),
-            uvs: attachment.uvs.unwrap_or(Vec::new()),
-            bone_indices: None,
+            path: attachment.path.or_else(|| name.clone()),
+            name,
+            color: Color::from_rgba_hex(&attachment.color).map_err(|e| e.at("color"))?,
+            triangles: attachment.triangles.unwrap_or_default(),
+            edges: attachment.edges.unwrap_or_default(),
+            vertices: attachment.vertices.unwrap_or_default(),
+            uvs: region_uvs.clone(),
+            region_uvs,
+            texture: None,
+            bones: None,
+            hull: attachment.hull.unwrap_or(0).max(0) as usize,
             is_weighted: false,
             world_vertices_length: 0,
         };
 
-        mesh.update_vertices();
+        mesh.update_vertices()
+            .map_err(|e| SkeletonError::from(e).at("vertices"))?;
+        mesh.scale_vertices(scale);
         mesh.update_uvs();
-        mesh
+        Ok(mesh)
     }
 
-    fn update_vertices(&mut self) {
-        let uvs_len = self.uvs.len();
-        let is_weighted_mesh = self.vertices.len() > uvs_len;
+    /// scales vertices positions (bind positions for weighted meshes)
+    fn scale_vertices(&mut self, scale: f32) {
+        if scale == 1.0 {
+            return;
+        }
+        if self.is_weighted {
+            // weighted vertices are stored as (bind_x, bind_y, weight)
+            for v in self.vertices.chunks_mut(3) {
+                v[0] *= scale;
+                v[1] *= scale;
+            }
+        } else {
+            for v in &mut self.vertices {
+                *v *= scale;
+            }
+        }
+    }
+
+    fn update_vertices(&mut self) -> Result<(), AttachmentError> {
+        self.world_vertices_length = self.region_uvs.len() as u32;
+        let is_weighted_mesh = self.vertices.len() > self.region_uvs.len();
 
         if !is_weighted_mesh {
-            return;
+            return Ok(());
         };
 
         self.is_weighted = is_weighted_mesh;
-        let mut weights: Vec<f32> = Vec::with_capacity(uvs_len * 3 * 3);
-        let mut bone_indices: Vec<u32> = Vec::with_capacity(uvs_len * 3);
+        let (weights, bones) = vertices::split_weighted(&self.vertices)?;
+        self.vertices = weights;
+        self.bones = Some(bones);
+        Ok(())
+    }
 
-        {
-            let mut item_iter = self.vertices.iter();
+    /// vertices of the hull, in world space
+    ///
+    /// `srts` are the world srts of the skeleton bones
+    pub fn world_hull(&self, srts: &[SRT], slot_bone: usize) -> Vec<[f32; 2]> {
+        let mut world = self.world_vertices(srts, slot_bone);
+        world.truncate(self.hull);
+        world
+    }
 
-            'items: loop {
-                if let Some(bone_count) = item_iter.next() {
-                    let mut bones_iter = item_iter.by_ref().take(*bone_count as usize * 4);
-                    'bones: loop {
-                        if let Some(bone_index) = bones_iter.next() {
-                            let bind_x = bones_iter.next().copied().unwrap();
-                            let bind_y = bones_iter.next().copied().unwrap();
-                            let weight = bones_iter.next().copied().unwrap();
+    /// vertices in world space
+    ///
+    /// `srts` are the world srts of the skeleton bones
+    pub fn world_vertices(&self, srts: &[SRT], slot_bone: usize) -> Vec<[f32; 2]> {
+        vertices::world_vertices(&self.vertices, self.bones.as_deref(), srts, slot_bone)
+    }
 
-                            bone_indices.push(*bone_index as u32);
-                            weights.push(bind_x);
-                            weights.push(bind_y);
-                            weights.push(weight);
-                        } else {
-                            break 'bones;
-                        }
-                    }
-                } else {
-                    break 'items;
-                }
+    /// Links the attachment to a texture region and computes its uvs
+    pub fn set_texture(&mut self, texture: TextureRegion) {
+        self.texture = Some(texture);
+        self.update_uvs();
+    }
+
+    /// converts region uvs into page uvs
+    fn update_uvs(&mut self) {
+        let texture = match self.texture {
+            Some(ref texture) => texture,
+            None => {
+                self.uvs = self.region_uvs.clone();
+                return;
+            }
+        };
+        let (u, v) = (texture.u, texture.v);
+        let (width, height) = (texture.u2 - texture.u, texture.v2 - texture.v);
+        self.uvs.clear();
+        for uv in self.region_uvs.chunks(2) {
+            if texture.rotate {
+                self.uvs.push(u + uv[1] * width);
+                self.uvs.push(v + height - uv[0] * height);
+            } else {
+                self.uvs.push(u + uv[0] * width);
+                self.uvs.push(v + uv[1] * height);
             }
         }
-
-        self.vertices = weights;
-        self.bone_indices = Some(bone_indices);
     }
-
-    fn update_uvs(&self) {}
 }
@@ -1,42 +1,94 @@
+pub mod bounding_box;
 pub mod mesh;
 pub mod region;
+pub mod texture;
+pub mod vertices;
 
+use self::bounding_box::BoundingBoxAttachment;
 use self::mesh::MeshAttachment;
 use self::region::RegionAttachment;
 use super::json;
 use super::json::AttachmentType;
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
+use std::error::Error;
+use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Attachment {
     Region(RegionAttachment),
     Mesh(MeshAttachment),
+    BoundingBox(BoundingBoxAttachment),
 }
 
 #[derive(Debug)]
 pub enum AttachmentError {
-    UnknownType,
+    /// attachment type is not supported
+    UnknownType(String),
+    /// a weighted vertex (index of its bone count) has more bones than the remaining values
+    InvalidVertices(usize),
+}
+
+impl fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AttachmentError::UnknownType(ref t) => write!(f, "Unsupported attachment type '{}'", t),
+            AttachmentError::InvalidVertices(i) => {
+                write!(f, "Weighted vertex at {} has too many bones", i)
+            }
+        }
+    }
 }
 
+impl Error for AttachmentError {}
+
 impl Attachment {
     pub fn name(&self) -> Option<&String> {
         match self {
             Attachment::Region(region) => region.name.as_ref(),
             Attachment::Mesh(mesh) => mesh.name.as_ref(),
+            Attachment::BoundingBox(bounding_box) => bounding_box.name.as_ref(),
+        }
+    }
+
+    /// tint of the attachment
+    pub fn color(&self) -> Color {
+        match self {
+            Attachment::Region(region) => region.color,
+            Attachment::Mesh(mesh) => mesh.color,
+            Attachment::BoundingBox(bounding_box) => bounding_box.color,
         }
     }
+
+    /// indices of the bones weighting the vertices, empty if not weighted
+    pub(crate) fn weighted_bones(&self) -> Vec<usize> {
+        let bones = match self {
+            Attachment::Region(_) => None,
+            Attachment::Mesh(mesh) => mesh.bones.as_deref(),
+            Attachment::BoundingBox(bounding_box) => bounding_box.bones.as_deref(),
+        };
+        bones.map(vertices::weighted_bones).unwrap_or_default()
+    }
+
     /// converts json data into skeleton data
     pub fn from_json(
         attachment: json::Attachment,
         name: Option<String>,
-    ) -> Result<Attachment, AttachmentError> {
+        scale: f32,
+    ) -> Result<Attachment, SkeletonError> {
         let t = attachment.type_.clone();
 
         match t.unwrap_or(AttachmentType::Region) {
             AttachmentType::Region => {
-                Ok(Attachment::Region(RegionAttachment::new(attachment, name)))
+                RegionAttachment::new(attachment, name, scale).map(Attachment::Region)
+            }
+            AttachmentType::Mesh => {
+                MeshAttachment::new(attachment, name, scale).map(Attachment::Mesh)
+            }
+            AttachmentType::BoundingBox => {
+                BoundingBoxAttachment::new(attachment, name, scale).map(Attachment::BoundingBox)
             }
-            AttachmentType::Mesh => Ok(Attachment::Mesh(MeshAttachment::new(attachment, name))),
-            _ => Err(AttachmentError::UnknownType),
+            t => Err(AttachmentError::UnknownType(t.to_string()).into()),
         }
     }
 }
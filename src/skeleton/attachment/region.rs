@@ -1,31 +1,75 @@
+use super::texture::TextureRegion;
 use json;
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
 use skeleton::srt::SRT;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RegionAttachment {
     pub name: Option<String>,
+    /// name of the texture region to draw
+    pub path: Option<String>,
     pub srt: SRT,
+    /// tint of the attachment, multiplied with the slot color
+    pub color: Color,
+    /// texture region, once resolved by an `AttachmentLoader`
+    pub texture: Option<TextureRegion>,
+    /// texture coordinates of the bottom-left, top-left, top-right and bottom-right corners
+    pub uvs: [f32; 8],
     // fps: Option<f32>,
     // mode: Option<String>,
     //vertices: Option<Vec<??>>     // TODO: ?
 }
 
 impl RegionAttachment {
-    pub fn new(attachment: json::Attachment, name: Option<String>) -> RegionAttachment {
+    pub fn new(
+        attachment: json::Attachment,
+        name: Option<String>,
+        scale: f32,
+    ) -> Result<RegionAttachment, SkeletonError> {
         let (w2, h2) = (
-            attachment.width.unwrap_or(0f32) * 0.5,
-            attachment.height.unwrap_or(0f32) * 0.5,
+            attachment.width.unwrap_or(0f32) * 0.5 * scale,
+            attachment.height.unwrap_or(0f32) * 0.5 * scale,
         );
+        let name = attachment.name.or(name);
 
-        RegionAttachment {
-            name: attachment.name.or(name),
+        Ok(RegionAttachment {
+            path: attachment.path.or_else(|| name.clone()),
+            name,
             srt: SRT::new(
                 attachment.scale_x.unwrap_or(1.0) * w2,
                 attachment.scale_y.unwrap_or(1.0) * h2,
                 attachment.rotation.unwrap_or(0.0),
-                attachment.x.unwrap_or(0.0),
-                attachment.y.unwrap_or(0.0),
+                attachment.x.unwrap_or(0.0) * scale,
+                attachment.y.unwrap_or(0.0) * scale,
             ),
+            color: Color::from_rgba_hex(&attachment.color).map_err(|e| e.at("color"))?,
+            texture: None,
+            uvs: [0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0],
+        })
+    }
+
+    /// Links the attachment to a texture region, computes its uvs and shrinks the quad
+    /// to the packed (whitespace stripped) part of the image
+    pub fn set_texture(&mut self, texture: TextureRegion) {
+        let (u, v, u2, v2) = (texture.u, texture.v, texture.u2, texture.v2);
+        self.uvs = if texture.rotate {
+            [u2, v2, u, v2, u, v, u2, v]
+        } else {
+            [u, v2, u, v, u2, v, u2, v2]
+        };
+
+        if texture.orig.0 > 0 && texture.orig.1 > 0 {
+            // packed rectangle in the unit square (-1, -1) (1, 1) covered by the srt
+            let (orig_w, orig_h) = (f32::from(texture.orig.0), f32::from(texture.orig.1));
+            let x0 = -1.0 + 2.0 * f32::from(texture.offset.0) / orig_w;
+            let y0 = -1.0 + 2.0 * f32::from(texture.offset.1) / orig_h;
+            let x1 = x0 + 2.0 * f32::from(texture.size.0) / orig_w;
+            let y1 = y0 + 2.0 * f32::from(texture.size.1) / orig_h;
+            self.srt.position = self.srt.transform([(x0 + x1) * 0.5, (y0 + y1) * 0.5]);
+            self.srt.scale[0] *= (x1 - x0) * 0.5;
+            self.srt.scale[1] *= (y1 - y0) * 0.5;
         }
+        self.texture = Some(texture);
     }
 }
@@ -0,0 +1,49 @@
+use atlas::Texture;
+
+/// Region of a texture page an attachment is drawn from
+#[derive(Debug, Clone)]
+pub struct TextureRegion {
+    /// texture page index (atlas page for the default loader, any engine specific handle
+    /// for custom loaders)
+    pub page: usize,
+    /// left texture coordinate
+    pub u: f32,
+    /// top texture coordinate
+    pub v: f32,
+    /// right texture coordinate
+    pub u2: f32,
+    /// bottom texture coordinate
+    pub v2: f32,
+    /// region is rotated 90 degrees in the page
+    pub rotate: bool,
+    /// packed size in pixels (whitespace stripped)
+    pub size: (u16, u16),
+    /// original size in pixels
+    pub orig: (u16, u16),
+    /// offset of the packed region in the original image, from bottom-left
+    pub offset: (u16, u16),
+}
+
+impl TextureRegion {
+    /// Creates a texture region from an atlas texture on a page of size `page_size`
+    pub fn from_atlas(texture: &Texture, page_size: (u16, u16), page: usize) -> TextureRegion {
+        let (page_w, page_h) = (f32::from(page_size.0), f32::from(page_size.1));
+        let (x, y) = (f32::from(texture.xy.0), f32::from(texture.xy.1));
+        let (w, h) = if texture.rotate {
+            (f32::from(texture.size.1), f32::from(texture.size.0))
+        } else {
+            (f32::from(texture.size.0), f32::from(texture.size.1))
+        };
+        TextureRegion {
+            page,
+            u: x / page_w,
+            v: y / page_h,
+            u2: (x + w) / page_w,
+            v2: (y + h) / page_h,
+            rotate: texture.rotate,
+            size: texture.size,
+            orig: texture.orig,
+            offset: texture.offset,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Module to handle vertices of meshes and bounding boxes, which can be weighted to several bones
+
+use super::AttachmentError;
+use skeleton::srt::SRT;
+
+/// Splits weighted json vertices (`bone count, (bone index, bind x, bind y, weight) * count`)
+/// into `(bind x, bind y, weight)` triples and bones (`bone count, bone index * count`)
+///
+/// Fails if a bone count needs more values than remain
+pub fn split_weighted(vertices: &[f32]) -> Result<(Vec<f32>, Vec<u32>), AttachmentError> {
+    let mut weights = Vec::with_capacity(vertices.len());
+    let mut bones = Vec::with_capacity(vertices.len() / 4);
+    let mut i = 0;
+    while let Some(&count) = vertices.get(i) {
+        let count = count as usize;
+        let values = match vertices.get(i + 1..) {
+            Some(values) if values.len() / 4 >= count => &values[..count * 4],
+            _ => return Err(AttachmentError::InvalidVertices(i)),
+        };
+        bones.push(count as u32);
+        for v in values.chunks(4) {
+            bones.push(v[0] as u32);
+            weights.extend_from_slice(&v[1..]);
+        }
+        i += 1 + count * 4;
+    }
+    Ok((weights, bones))
+}
+
+/// Indices of the bones weighting vertices (see `split_weighted`), with duplicates
+pub fn weighted_bones(bones: &[u32]) -> Vec<usize> {
+    let mut indices = Vec::new();
+    let mut b = 0;
+    while b < bones.len() {
+        let count = bones[b] as usize;
+        indices.extend(bones.iter().skip(b + 1).take(count).map(|&i| i as usize));
+        b += count + 1;
+    }
+    indices
+}
+
+/// Computes world positions of vertices
+///
+/// `vertices` are relative to the slot bone if `bones` is `None`, else weighted
+/// (see `split_weighted`). Bones missing from `srts` are ignored.
+pub fn world_vertices(
+    vertices: &[f32],
+    bones: Option<&[u32]>,
+    srts: &[SRT],
+    slot_bone: usize,
+) -> Vec<[f32; 2]> {
+    let bones = match bones {
+        Some(bones) => bones,
+        None => {
+            return match srts.get(slot_bone) {
+                Some(srt) => vertices
+                    .chunks(2)
+                    .filter(|v| v.len() == 2)
+                    .map(|v| srt.transform([v[0], v[1]]))
+                    .collect(),
+                None => Vec::new(),
+            };
+        }
+    };
+
+    let mut world = Vec::new();
+    let (mut b, mut w) = (0, 0);
+    while b < bones.len() {
+        let count = bones[b] as usize;
+        let mut position = [0f32; 2];
+        for &bone in bones.iter().skip(b + 1).take(count) {
+            if let (Some(srt), Some(v)) = (srts.get(bone as usize), vertices.get(w..w + 3)) {
+                let p = srt.transform([v[0], v[1]]);
+                position[0] += p[0] * v[2];
+                position[1] += p[1] * v[2];
+            }
+            w += 3;
+        }
+        world.push(position);
+        b += count + 1;
+    }
+    world
+}
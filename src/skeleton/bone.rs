@@ -7,35 +7,43 @@ use super::SkeletonError;
 pub struct Bone {
     pub name: String,
     pub parent_index: Option<usize>,
-    // length: f32,
+    /// length of the bone, along its x axis
+    pub length: f32,
     pub srt: SRT,
     pub inherit_scale: bool,
     pub inherit_rotation: bool,
     pub transform_mode: TransformMode,
+    /// bone is only active when the skin requires it
+    pub skin_required: bool,
 }
 
 impl Bone {
-    pub fn from_json(bone: json::Bone, bones: &[Bone]) -> Result<Bone, SkeletonError> {
+    pub fn from_json(
+        bone: json::Bone,
+        bones: &util::Names,
+        scale: f32,
+    ) -> Result<Bone, SkeletonError> {
         let index = match bone.parent {
-            Some(ref name) => Some(util::bone_index(name, bones)?),
+            Some(ref name) => Some(util::bone_index(name, bones).map_err(|e| e.at("parent"))?),
             None => None,
         };
         Ok(Bone {
             transform_mode: bone
                 .transform
-                .map_or(TransformMode::Normal, |mode| TransformMode::from(mode)),
+                .map_or(TransformMode::Normal, TransformMode::from),
             name: bone.name,
             parent_index: index,
-            // length: bone.length.unwrap_or(0f32),
+            length: bone.length.unwrap_or(0f32) * scale,
             srt: SRT::new(
                 bone.scale_x.unwrap_or(1.0),
                 bone.scale_y.unwrap_or(1.0),
                 bone.rotation.unwrap_or(0.0),
-                bone.x.unwrap_or(0.0),
-                bone.y.unwrap_or(0.0),
+                bone.x.unwrap_or(0.0) * scale,
+                bone.y.unwrap_or(0.0) * scale,
             ),
             inherit_scale: bone.inherit_scale.unwrap_or(true),
             inherit_rotation: bone.inherit_rotation.unwrap_or(true),
+            skin_required: bone.skin.unwrap_or(false),
         })
     }
 }
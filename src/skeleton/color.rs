@@ -0,0 +1,104 @@
+//! Module to handle RGBA colors
+
+use super::SkeletonError;
+use rustc_hex::FromHex;
+use std::ops::Mul;
+
+/// RGBA color, channels from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    /// red
+    pub r: f32,
+    /// green
+    pub g: f32,
+    /// blue
+    pub b: f32,
+    /// alpha
+    pub a: f32,
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color::WHITE
+    }
+}
+
+impl Color {
+    /// opaque white, neutral for color composition
+    pub const WHITE: Color = Color {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    };
+
+    /// opaque black
+    pub const BLACK: Color = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+
+    /// new color
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+
+    /// converts 8 bits channels
+    pub fn from_rgba8(rgba: [u8; 4]) -> Color {
+        Color::new(
+            f32::from(rgba[0]) / 255.0,
+            f32::from(rgba[1]) / 255.0,
+            f32::from(rgba[2]) / 255.0,
+            f32::from(rgba[3]) / 255.0,
+        )
+    }
+
+    /// converts to 8 bits channels, rounded to the nearest value
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let c = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [c(self.r), c(self.g), c(self.b), c(self.a)]
+    }
+
+    /// parses a `RRGGBBAA` color
+    pub fn from_rgba_hex(color: &str) -> Result<Color, SkeletonError> {
+        let v: Vec<u8> = color
+            .from_hex()
+            .map_err(|_| SkeletonError::InvalidColor(color.to_owned()))?;
+        if v.len() != 4 {
+            return Err(SkeletonError::InvalidColor(color.to_owned()));
+        }
+        Ok(Color::from_rgba8([v[0], v[1], v[2], v[3]]))
+    }
+
+    /// parses a `RRGGBB` color (opaque)
+    pub fn from_rgb_hex(color: &str) -> Result<Color, SkeletonError> {
+        let v: Vec<u8> = color
+            .from_hex()
+            .map_err(|_| SkeletonError::InvalidColor(color.to_owned()))?;
+        if v.len() != 3 {
+            return Err(SkeletonError::InvalidColor(color.to_owned()));
+        }
+        Ok(Color::from_rgba8([v[0], v[1], v[2], 255]))
+    }
+
+    /// multiplies rgb channels by alpha
+    pub fn premultiply(&self) -> Color {
+        Color::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+}
+
+/// component-wise multiplication
+impl Mul for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        Color::new(
+            self.r * other.r,
+            self.g * other.g,
+            self.b * other.b,
+            self.a * other.a,
+        )
+    }
+}
@@ -0,0 +1,57 @@
+//! Module for ik, transform and path constraints
+//!
+//! Constraints are kept to know the bones they use, they are not applied to poses.
+
+use super::json;
+use super::util;
+use super::SkeletonError;
+
+/// Kind of a constraint, as the json list it is defined in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    Ik,
+    Transform,
+    Path,
+}
+
+/// skeleton constraint
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub name: String,
+    pub kind: ConstraintKind,
+    /// indices of the constrained bones
+    pub bones: Vec<usize>,
+    /// index of the target bone, or of the target slot for path constraints
+    pub target: usize,
+}
+
+impl Constraint {
+    pub fn from_json(
+        constraint: json::Constraint,
+        kind: ConstraintKind,
+        bones: &util::Names,
+        slots: &util::Names,
+    ) -> Result<Constraint, SkeletonError> {
+        let target = match kind {
+            ConstraintKind::Path => util::slot_index(&constraint.target, slots),
+            _ => util::bone_index(&constraint.target, bones),
+        }
+        .map_err(|e| e.at("target"))?;
+        let bones = constraint
+            .bones
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                util::bone_index(name, bones).map_err(|e| e.at(&format!("bones[{}]", i)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Constraint {
+            name: constraint.name,
+            kind,
+            bones,
+            target,
+        })
+    }
+}
@@ -1,13 +1,15 @@
 //! Module to handle all spine errors
 
-use rustc_hex::FromHexError;
 use serde_json::error::Error as SerdeError;
+use serde_path_to_error::Error as PathError;
+use skeleton::attachment::AttachmentError;
 use std::error::Error;
 use std::fmt;
 
-/// Error that can happen while calculating an animation.
+/// Error that can happen while loading a skeleton or calculating an animation.
+#[derive(Debug)]
 pub enum SkeletonError {
-    /// Parser error
+    /// Parser error, in a `Context` with the path of the value being read
     ParserError(SerdeError),
 
     /// The requested bone was not found.
@@ -16,57 +18,133 @@ pub enum SkeletonError {
     /// The requested slot was not found.
     SlotNotFound(String),
 
-    /// The requested slot was not found.
+    /// The requested skin was not found.
     SkinNotFound(String),
 
-    /// The requested slot was not found.
-    InvalidColor(FromHexError),
+    /// The requested constraint was not found.
+    ConstraintNotFound(String),
+
+    /// A color (held) is not made of hexadecimal digits `RRGGBBAA` (or `RRGGBB` for dark colors).
+    InvalidColor(String),
 
     /// The requested animation was not found.
     AnimationNotFound(String),
+
+    /// Several bones, slots or skins share a name.
+    DuplicateName(String),
+
+    /// An attachment cannot be loaded.
+    InvalidAttachment(AttachmentError),
+
+    /// The texture region of an attachment was not found.
+    TextureNotFound(String),
+
+    /// The document was exported by an unsupported version of Spine.
+    UnsupportedVersion(String),
+
+    /// A bezier curve does not have 4 values per channel (number of values found).
+    InvalidCurve(usize),
+
+    /// An error caused by a value of the json document, with the path of the value
+    /// (e.g. `animations.walk.bones.leg.rotate[3]`).
+    Context(String, Box<SkeletonError>),
+}
+
+impl SkeletonError {
+    /// Adds a segment in front of the json path of the error
+    pub(crate) fn at(self, segment: &str) -> SkeletonError {
+        match self {
+            SkeletonError::Context(path, error) => {
+                let separator = if path.starts_with('[') { "" } else { "." };
+                SkeletonError::Context(format!("{}{}{}", segment, separator, path), error)
+            }
+            error => SkeletonError::Context(segment.to_owned(), Box::new(error)),
+        }
+    }
+
+    /// Path of the json value which caused the error, if known
+    pub fn path(&self) -> Option<&str> {
+        match *self {
+            SkeletonError::Context(ref path, _) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Error without its path
+    pub fn kind(&self) -> &SkeletonError {
+        match *self {
+            SkeletonError::Context(_, ref error) => error.kind(),
+            ref error => error,
+        }
+    }
 }
 
-impl fmt::Debug for SkeletonError {
+impl fmt::Display for SkeletonError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             SkeletonError::BoneNotFound(ref name) => write!(f, "Cannot find bone '{}'", name),
             SkeletonError::SlotNotFound(ref name) => write!(f, "Cannot find slot '{}'", name),
             SkeletonError::SkinNotFound(ref name) => write!(f, "Cannot find skin '{}'", name),
+            SkeletonError::ConstraintNotFound(ref name) => {
+                write!(f, "Cannot find constraint '{}'", name)
+            }
             SkeletonError::AnimationNotFound(ref name) => {
                 write!(f, "Cannot find animation '{}'", name)
             }
-            SkeletonError::InvalidColor(ref e) => {
-                write!(f, "Cannot convert color to hexadecimal: {:?}", e)
+            SkeletonError::DuplicateName(ref name) => write!(f, "Duplicate name '{}'", name),
+            SkeletonError::InvalidColor(ref color) => write!(
+                f,
+                "Invalid color '{}': expecting hexadecimal RRGGBBAA or RRGGBB",
+                color
+            ),
+            SkeletonError::ParserError(ref e) => write!(f, "Cannot deserialize from json: {}", e),
+            SkeletonError::InvalidAttachment(ref e) => write!(f, "{}", e),
+            SkeletonError::TextureNotFound(ref name) => {
+                write!(f, "Cannot find texture region '{}'", name)
+            }
+            SkeletonError::UnsupportedVersion(ref version) => {
+                write!(f, "Unsupported spine version '{}'", version)
             }
-            SkeletonError::ParserError(ref e) => write!(f, "Cannot deserialize from json: {:?}", e),
+            SkeletonError::InvalidCurve(len) => write!(
+                f,
+                "Invalid bezier curve: expecting 4 values per channel, found {}",
+                len
+            ),
+            SkeletonError::Context(ref path, ref e) => write!(f, "{}: {}", path, e),
         }
     }
 }
 
-impl fmt::Display for SkeletonError {
-    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&self, formatter)
-    }
-}
-
 impl Error for SkeletonError {
     fn description(&self) -> &str {
         match *self {
             SkeletonError::BoneNotFound(_) => "bone cannot be found in skeleton bones",
             SkeletonError::SlotNotFound(_) => "slot cannot be found in skeleton slots",
             SkeletonError::SkinNotFound(_) => "skin cannot be found in skeleton skins",
+            SkeletonError::ConstraintNotFound(_) => {
+                "constraint cannot be found in skeleton constraints"
+            }
             SkeletonError::InvalidColor(_) => "color cannot be parsed",
             SkeletonError::AnimationNotFound(_) => {
                 "animation cannot be found in skeleton animations"
             }
+            SkeletonError::DuplicateName(_) => "name is already used",
             SkeletonError::ParserError(_) => "error while parsing json skeleton",
+            SkeletonError::InvalidAttachment(_) => "attachment cannot be loaded",
+            SkeletonError::TextureNotFound(_) => "texture region cannot be found in atlas",
+            SkeletonError::UnsupportedVersion(_) => "spine version is not supported",
+            SkeletonError::InvalidCurve(_) => "bezier curve has an invalid number of values",
+            SkeletonError::Context(..) => "invalid value in json document",
         }
     }
-}
 
-impl From<FromHexError> for SkeletonError {
-    fn from(error: FromHexError) -> SkeletonError {
-        SkeletonError::InvalidColor(error)
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SkeletonError::ParserError(ref e) => Some(e),
+            SkeletonError::InvalidAttachment(ref e) => Some(e),
+            SkeletonError::Context(_, ref e) => Some(&**e),
+            _ => None,
+        }
     }
 }
 
@@ -75,3 +153,21 @@ impl From<SerdeError> for SkeletonError {
         SkeletonError::ParserError(error)
     }
 }
+
+impl From<PathError<SerdeError>> for SkeletonError {
+    fn from(error: PathError<SerdeError>) -> SkeletonError {
+        let path = error.path().to_string();
+        let error = SkeletonError::ParserError(error.into_inner());
+        if path == "." {
+            error
+        } else {
+            error.at(&path)
+        }
+    }
+}
+
+impl From<AttachmentError> for SkeletonError {
+    fn from(error: AttachmentError) -> SkeletonError {
+        SkeletonError::InvalidAttachment(error)
+    }
+}
@@ -0,0 +1,29 @@
+//! Typed indices of the skeleton data, returned by `Skeleton::find_*`
+
+macro_rules! handle {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub(crate) usize);
+
+        impl $name {
+            /// index in the skeleton data
+            pub fn index(self) -> usize {
+                self.0
+            }
+        }
+    };
+}
+
+handle!(
+    /// Handle to a bone of a skeleton
+    BoneHandle
+);
+handle!(
+    /// Handle to a slot of a skeleton
+    SlotHandle
+);
+handle!(
+    /// Handle to an animation of a skeleton
+    AnimationHandle
+);
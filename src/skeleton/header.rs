@@ -0,0 +1,102 @@
+//! Module to handle the `skeleton` section of a document
+
+use super::error::SkeletonError;
+use json;
+use std::fmt;
+
+/// Version of the Spine editor which exported the document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    /// major version
+    pub major: u32,
+    /// minor version
+    pub minor: u32,
+    /// patch version
+    pub patch: u32,
+}
+
+impl Version {
+    /// Parses a version like `3.8.99` (missing numbers default to 0 and any suffix is ignored)
+    pub fn parse(version: &str) -> Result<Version, SkeletonError> {
+        let mut numbers = version.trim().split('.').map(|n| {
+            let digits = n.bytes().take_while(u8::is_ascii_digit).count();
+            n[..digits].parse::<u32>()
+        });
+        let mut next = || match numbers.next() {
+            Some(Ok(n)) => Ok(n),
+            None => Ok(0),
+            Some(Err(_)) => Err(SkeletonError::UnsupportedVersion(version.to_owned())),
+        };
+        Ok(Version {
+            major: next()?,
+            minor: next()?,
+            patch: next()?,
+        })
+    }
+
+    /// Checks if the json schema of this version can be loaded (3.6 to 4.x)
+    pub fn is_supported(&self) -> bool {
+        match (self.major, self.minor) {
+            (3, minor) => minor >= 6,
+            (4, _) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Skeleton header
+#[derive(Debug, Clone)]
+pub struct Header {
+    /// hash of the skeleton data
+    pub hash: Option<String>,
+    /// version of the Spine editor, `None` if the document does not define it
+    pub version: Option<Version>,
+    /// x position of the setup pose bounding box
+    pub x: f32,
+    /// y position of the setup pose bounding box
+    pub y: f32,
+    /// width of the setup pose bounding box
+    pub width: f32,
+    /// height of the setup pose bounding box
+    pub height: f32,
+    /// dopesheet framerate, in frames per second
+    pub fps: f32,
+    /// path to the images directory
+    pub images: Option<String>,
+    /// path to the audio directory
+    pub audio: Option<String>,
+}
+
+impl Header {
+    /// Converts json header, checking that the version is supported
+    pub fn from_json(header: Option<json::SkeletonHeader>) -> Result<Header, SkeletonError> {
+        let header = header.unwrap_or_default();
+        let version = match header.spine {
+            Some(ref v) => {
+                let version = Version::parse(v).map_err(|e| e.at("skeleton.spine"))?;
+                if !version.is_supported() {
+                    return Err(SkeletonError::UnsupportedVersion(v.clone()).at("skeleton.spine"));
+                }
+                Some(version)
+            }
+            None => None,
+        };
+        Ok(Header {
+            hash: header.hash,
+            version,
+            x: header.x.unwrap_or(0.0),
+            y: header.y.unwrap_or(0.0),
+            width: header.width.unwrap_or(0.0),
+            height: header.height.unwrap_or(0.0),
+            fps: header.fps.unwrap_or(30.0),
+            images: header.images,
+            audio: header.audio,
+        })
+    }
+}
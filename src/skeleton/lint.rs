@@ -0,0 +1,220 @@
+//! Module to report problems of a loaded skeleton which do not prevent it from loading
+//!
+//! Problems are located by the path of the faulty value in the json document
+//! (e.g. `animations.walk.bones.leg.rotate[3]`).
+
+use super::attachment::Attachment;
+use super::constraint::ConstraintKind;
+use super::skin::Skin;
+use super::Skeleton;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A problem found by `Skeleton::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lint {
+    /// A key is before the previous key of its timeline
+    KeysOutOfOrder(String),
+    /// An attachment timeline names an attachment (name) missing from all skins, or from the
+    /// validated skin and the default skin
+    MissingAttachment(String, String),
+    /// A bone has no slot, in its own or its children slots, weights no vertex and is not used
+    /// by a constraint
+    UnusedBone(String),
+    /// A slot has no attachment in any skin
+    UnusedSlot(String),
+    /// An attachment is neither the setup attachment of its slot nor animated
+    UnusedAttachment(String),
+    /// A bezier curve joins two keys at the same time
+    ZeroLengthCurve(String),
+    /// Several bones, slots or skins share a name, only the first one can be found by name (non
+    /// strict loading only)
+    DuplicateName(String),
+    /// A mesh triangle references a vertex (index) beyond its vertices
+    InvalidTriangle(String, usize),
+    /// A timeline is loaded but not applied (bone shears are not part of `SRT`)
+    IgnoredTimeline(String),
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Lint::KeysOutOfOrder(ref path) => write!(f, "{}: key is before the previous key", path),
+            Lint::MissingAttachment(ref path, ref name) => {
+                write!(f, "{}: attachment '{}' cannot be found", path, name)
+            }
+            Lint::UnusedBone(ref path) => write!(f, "{}: bone is not used", path),
+            Lint::UnusedSlot(ref path) => write!(f, "{}: slot has no attachment", path),
+            Lint::UnusedAttachment(ref path) => write!(f, "{}: attachment is never shown", path),
+            Lint::ZeroLengthCurve(ref path) => {
+                write!(f, "{}: bezier curve to a key at the same time", path)
+            }
+            Lint::DuplicateName(ref path) => write!(f, "{}: name is already used", path),
+            Lint::InvalidTriangle(ref path, index) => {
+                write!(f, "{}: triangle vertex {} does not exist", path, index)
+            }
+            Lint::IgnoredTimeline(ref path) => write!(f, "{}: timeline is not applied", path),
+        }
+    }
+}
+
+/// Problems found in a skeleton
+#[derive(Debug, Clone, Default)]
+pub struct LintReport {
+    /// all the problems found
+    pub lints: Vec<Lint>,
+}
+
+impl LintReport {
+    /// Checks if no problem was found
+    pub fn is_empty(&self) -> bool {
+        self.lints.is_empty()
+    }
+}
+
+/// One problem per line
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for lint in &self.lints {
+            writeln!(f, "{}", lint)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs all the checks on a loaded skeleton, after the lints found while loading it
+///
+/// Attachment keys are searched in `skins`, all the skins if `None`
+pub(crate) fn validate(skeleton: &Skeleton, skins: Option<&[&Skin]>) -> LintReport {
+    let mut lints = skeleton.lints.clone();
+    animations(skeleton, skins, &mut lints);
+    meshes(skeleton, &mut lints);
+    if !skeleton.skins.is_empty() {
+        unused_slots(skeleton, &mut lints);
+        unused_attachments(skeleton, &mut lints);
+        unused_bones(skeleton, &mut lints);
+    }
+    LintReport { lints }
+}
+
+/// invalid keys and attachment keys missing from skins
+fn animations(skeleton: &Skeleton, skins: Option<&[&Skin]>, lints: &mut Vec<Lint>) {
+    let all = skeleton.skins.values().collect::<Vec<_>>();
+    let skins = skins.unwrap_or(&all);
+    for animation in &skeleton.animations {
+        let path = format!("animations.{}", animation.name);
+        for (bone, timeline) in animation.bone_timelines() {
+            let name = &skeleton.bones[bone.0].name;
+            timeline.lint(&format!("{}.bones.{}", path, name), lints);
+        }
+        for (slot, timeline) in animation.slot_timelines() {
+            let slot_path = format!("{}.slots.{}", path, skeleton.slots[slot.0].name);
+            timeline.lint(&slot_path, lints);
+            if skeleton.skins.is_empty() {
+                continue;
+            }
+            for name in timeline.get_attachment_names() {
+                if !skins.iter().any(|s| s.find(slot.0, name).is_some()) {
+                    lints.push(Lint::MissingAttachment(
+                        format!("{}.attachment", slot_path),
+                        name.to_owned(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// mesh triangles referencing missing vertices
+fn meshes(skeleton: &Skeleton, lints: &mut Vec<Lint>) {
+    for (skin_name, skin) in &skeleton.skins {
+        for (slot, attachments) in skeleton.slots.iter().zip(&skin.slots) {
+            for (name, attachment) in attachments {
+                let mesh = match *attachment {
+                    Attachment::Mesh(ref mesh) => mesh,
+                    _ => continue,
+                };
+                let vertex_count = mesh.region_uvs.len() / 2;
+                for (i, &index) in mesh.triangles.iter().enumerate() {
+                    if index as usize >= vertex_count {
+                        let path = format!(
+                            "skins.{}.{}.{}.triangles[{}]",
+                            skin_name, slot.name, name, i
+                        );
+                        lints.push(Lint::InvalidTriangle(path, index as usize));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// slots without attachment in any skin
+fn unused_slots(skeleton: &Skeleton, lints: &mut Vec<Lint>) {
+    for i in 0..skeleton.slots.len() {
+        let used = skeleton
+            .skins
+            .values()
+            .any(|s| s.slots.get(i).is_some_and(|a| !a.is_empty()));
+        if !used {
+            lints.push(Lint::UnusedSlot(format!("slots[{}]", i)));
+        }
+    }
+}
+
+/// attachments neither shown in setup pose nor by an attachment timeline
+fn unused_attachments(skeleton: &Skeleton, lints: &mut Vec<Lint>) {
+    let mut animated = HashSet::new();
+    for animation in &skeleton.animations {
+        for (slot, timeline) in animation.slot_timelines() {
+            for name in timeline.get_attachment_names() {
+                animated.insert((slot.0, name));
+            }
+        }
+    }
+    for (skin_name, skin) in &skeleton.skins {
+        for (i, (slot, attachments)) in skeleton.slots.iter().zip(&skin.slots).enumerate() {
+            for name in attachments.keys() {
+                let setup = slot.attachment.as_ref() == Some(name);
+                if !setup && !animated.contains(&(i, &**name)) {
+                    let path = format!("skins.{}.{}.{}", skin_name, slot.name, name);
+                    lints.push(Lint::UnusedAttachment(path));
+                }
+            }
+        }
+    }
+}
+
+/// bones without slot in their hierarchy and not weighting any vertex
+fn unused_bones(skeleton: &Skeleton, lints: &mut Vec<Lint>) {
+    let mut used = vec![false; skeleton.bones.len()];
+    for slot in &skeleton.slots {
+        used[slot.bone_index] = true;
+    }
+    for constraint in &skeleton.constraints {
+        let target = match constraint.kind {
+            ConstraintKind::Path => skeleton.slots.get(constraint.target).map(|s| s.bone_index),
+            _ => Some(constraint.target),
+        };
+        for bone in constraint.bones.iter().cloned().chain(target) {
+            used[bone] = true;
+        }
+    }
+    for skin in skeleton.skins.values() {
+        for attachment in skin.slots.iter().flat_map(|a| a.values()) {
+            for bone in attachment.weighted_bones() {
+                if let Some(used) = used.get_mut(bone) {
+                    *used = true;
+                }
+            }
+        }
+    }
+    // children come after their parents
+    for (i, bone) in skeleton.bones.iter().enumerate().rev() {
+        match bone.parent_index {
+            Some(parent) if used[i] => used[parent] = true,
+            Some(_) => lints.push(Lint::UnusedBone(format!("bones[{}]", i))),
+            None => (),
+        }
+    }
+}
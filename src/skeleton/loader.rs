@@ -0,0 +1,434 @@
+//! Module to configure how a skeleton is loaded
+
+use super::animation::Animation;
+use super::attachment::mesh::MeshAttachment;
+use super::attachment::region::RegionAttachment;
+use super::attachment::texture::TextureRegion;
+use super::attachment::{Attachment, AttachmentError};
+use super::bone::Bone;
+use super::constraint::{Constraint, ConstraintKind};
+use super::error::SkeletonError;
+use super::header::{Header, Version};
+use super::lint::Lint;
+use super::skin::Skin;
+use super::slot::Slot;
+use super::timeline::CurveEvaluation;
+use super::util;
+use super::Skeleton;
+use atlas::{Atlas, AtlasError, Texture};
+use json;
+use serde_json;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Hook invoked on every attachment once it has been created, typically to link it to a
+/// texture
+///
+/// Closures taking the skin name, the slot name and the attachment implement this trait
+/// (arguments types must be annotated).
+pub trait AttachmentLoader {
+    /// Resolves an attachment of slot `slot` in skin `skin`
+    ///
+    /// Dispatches to `load_region` or `load_mesh` by default (bounding boxes have no texture)
+    fn load(
+        &mut self,
+        skin: &str,
+        slot: &str,
+        attachment: &mut Attachment,
+    ) -> Result<(), SkeletonError> {
+        match *attachment {
+            Attachment::Region(ref mut region) => self.load_region(skin, slot, region),
+            Attachment::Mesh(ref mut mesh) => self.load_mesh(skin, slot, mesh),
+            Attachment::BoundingBox(_) => Ok(()),
+        }
+    }
+
+    /// Resolves a region attachment
+    fn load_region(
+        &mut self,
+        _skin: &str,
+        _slot: &str,
+        _region: &mut RegionAttachment,
+    ) -> Result<(), SkeletonError> {
+        Ok(())
+    }
+
+    /// Resolves a mesh attachment
+    fn load_mesh(
+        &mut self,
+        _skin: &str,
+        _slot: &str,
+        _mesh: &mut MeshAttachment,
+    ) -> Result<(), SkeletonError> {
+        Ok(())
+    }
+}
+
+impl<F> AttachmentLoader for F
+where
+    F: FnMut(&str, &str, &mut Attachment) -> Result<(), SkeletonError>,
+{
+    fn load(
+        &mut self,
+        skin: &str,
+        slot: &str,
+        attachment: &mut Attachment,
+    ) -> Result<(), SkeletonError> {
+        self(skin, slot, attachment)
+    }
+}
+
+/// Attachment loader linking region and mesh attachments to the textures of atlas pages
+///
+/// `TextureRegion::page` is the index of the page in the atlas
+pub struct AtlasAttachmentLoader {
+    page_sizes: Vec<(u16, u16)>,
+    textures: HashMap<String, Texture>,
+}
+
+impl AtlasAttachmentLoader {
+    /// Consumes an atlas and returns a loader resolving attachments against its textures
+    pub fn new<R: Read>(mut atlas: Atlas<R>) -> Result<AtlasAttachmentLoader, AtlasError> {
+        let mut textures = HashMap::new();
+        for texture in atlas.by_ref() {
+            let texture = texture?;
+            textures.insert(texture.name.clone(), texture);
+        }
+        Ok(AtlasAttachmentLoader {
+            page_sizes: atlas.pages().iter().map(|p| p.size).collect(),
+            textures,
+        })
+    }
+
+    /// Finds the texture region named `path`
+    fn find(&self, path: Option<&String>) -> Result<TextureRegion, SkeletonError> {
+        let path = path.map_or("", |p| &**p);
+        self.textures
+            .get(path)
+            .map(|t| TextureRegion::from_atlas(t, self.page_sizes[t.page], t.page))
+            .ok_or_else(|| SkeletonError::TextureNotFound(path.to_owned()))
+    }
+}
+
+impl AttachmentLoader for AtlasAttachmentLoader {
+    fn load_region(
+        &mut self,
+        _skin: &str,
+        _slot: &str,
+        region: &mut RegionAttachment,
+    ) -> Result<(), SkeletonError> {
+        let texture = self.find(region.path.as_ref())?;
+        region.set_texture(texture);
+        Ok(())
+    }
+
+    fn load_mesh(
+        &mut self,
+        _skin: &str,
+        _slot: &str,
+        mesh: &mut MeshAttachment,
+    ) -> Result<(), SkeletonError> {
+        let texture = self.find(mesh.path.as_ref())?;
+        mesh.set_texture(texture);
+        Ok(())
+    }
+}
+
+/// Skeleton loader with custom options
+///
+/// ```no_run
+/// # use std::fs::File;
+/// use spine::atlas::Atlas;
+/// use spine::skeleton::loader::{AtlasAttachmentLoader, SkeletonLoader};
+///
+/// let atlas = Atlas::from_reader(File::open("skeleton.atlas").unwrap()).unwrap();
+/// let skeleton = SkeletonLoader::new()
+///     .scale(0.5)
+///     .skip_animations(true)
+///     .attachment_loader(AtlasAttachmentLoader::new(atlas).unwrap())
+///     .load(File::open("skeleton.json").unwrap())
+///     .unwrap();
+/// ```
+pub struct SkeletonLoader {
+    scale: f32,
+    curves: CurveEvaluation,
+    strict: bool,
+    skip_animations: bool,
+    skip_skins: bool,
+    attachment_loader: Option<Box<dyn AttachmentLoader>>,
+}
+
+impl Default for SkeletonLoader {
+    fn default() -> SkeletonLoader {
+        SkeletonLoader {
+            scale: 1.0,
+            curves: CurveEvaluation::default(),
+            strict: true,
+            skip_animations: false,
+            skip_skins: false,
+            attachment_loader: None,
+        }
+    }
+}
+
+/// Takes the header of the document and converts the document to the 3.6/3.7 layout depending
+/// on its version
+pub(crate) fn upgrade(doc: &mut json::Document) -> Result<Header, SkeletonError> {
+    let header = Header::from_json(doc.skeleton.take())?;
+    match header.version {
+        Some(Version {
+            major: 3, minor: 8, ..
+        }) => json::compat::upgrade_3_8(doc),
+        Some(Version { major: 4, .. }) => json::compat::upgrade_4(doc)?,
+        _ => (),
+    }
+    Ok(header)
+}
+
+impl SkeletonLoader {
+    /// Creates a new loader with default options
+    pub fn new() -> SkeletonLoader {
+        SkeletonLoader::default()
+    }
+
+    /// Multiplies all lengths (bone positions, attachment offsets and sizes, mesh vertices and
+    /// translate keys) by `scale`
+    pub fn scale(mut self, scale: f32) -> SkeletonLoader {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets how bezier curves are evaluated (`CurveEvaluation::Segments` by default)
+    pub fn curve_evaluation(mut self, curves: CurveEvaluation) -> SkeletonLoader {
+        self.curves = curves;
+        self
+    }
+
+    /// Returns an error on unsupported attachment types and duplicate bone, slot or skin names if
+    /// `true` (default). Otherwise skips unsupported attachments and keeps the first bone, slot
+    /// or skin of a name, reporting the others in `Skeleton::validate`
+    pub fn strict(mut self, strict: bool) -> SkeletonLoader {
+        self.strict = strict;
+        self
+    }
+
+    /// Does not load any animation if `true`
+    pub fn skip_animations(mut self, skip: bool) -> SkeletonLoader {
+        self.skip_animations = skip;
+        self
+    }
+
+    /// Does not load any skin (and thus any attachment) if `true`
+    pub fn skip_skins(mut self, skip: bool) -> SkeletonLoader {
+        self.skip_skins = skip;
+        self
+    }
+
+    /// Sets a loader invoked on every loaded attachment, e.g. to resolve it against an atlas
+    pub fn attachment_loader<L>(mut self, loader: L) -> SkeletonLoader
+    where
+        L: AttachmentLoader + 'static,
+    {
+        self.attachment_loader = Some(Box::new(loader));
+        self
+    }
+
+    /// Consumes reader (with json data) and returns a skeleton
+    pub fn load<R: Read>(&mut self, mut reader: R) -> Result<Skeleton, SkeletonError> {
+        // read and convert as json
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut reader);
+        let document: json::Document = serde_path_to_error::deserialize(&mut deserializer)?;
+        deserializer.end()?;
+
+        // convert to skeleton (consumes document)
+        self.load_json(document)
+    }
+
+    /// Fails on a bone, slot or skin sharing the name of a previous one, or reports it if not
+    /// strict
+    fn duplicate_name(
+        &self,
+        name: &str,
+        path: String,
+        lints: &mut Vec<Lint>,
+    ) -> Result<(), SkeletonError> {
+        if self.strict {
+            return Err(SkeletonError::DuplicateName(name.to_owned()).at(&path));
+        }
+        lints.push(Lint::DuplicateName(path));
+        Ok(())
+    }
+
+    /// Converts a json::Document into a skeleton
+    fn load_json(&mut self, mut doc: json::Document) -> Result<Skeleton, SkeletonError> {
+        let scale = self.scale;
+
+        let header = upgrade(&mut doc)?;
+
+        let mut lints = Vec::new();
+        let (mut bones, mut bone_names) = (Vec::new(), util::Names::new());
+        if let Some(jbones) = doc.bones {
+            for (i, b) in jbones.into_iter().enumerate() {
+                let path = format!("bones[{}]", i);
+                let bone = Bone::from_json(b, &bone_names, scale).map_err(|e| e.at(&path))?;
+                if !util::insert_name(&mut bone_names, &bone.name, bones.len()) {
+                    self.duplicate_name(&bone.name, path, &mut lints)?;
+                }
+                bones.push(bone);
+            }
+        }
+
+        let (mut slots, mut slot_names) = (Vec::new(), util::Names::new());
+        if let Some(jslots) = doc.slots {
+            for (i, s) in jslots.into_iter().enumerate() {
+                let path = format!("slots[{}]", i);
+                let slot = Slot::from_json(s, &bone_names).map_err(|e| e.at(&path))?;
+                if !util::insert_name(&mut slot_names, &slot.name, slots.len()) {
+                    self.duplicate_name(&slot.name, path, &mut lints)?;
+                }
+                slots.push(slot);
+            }
+        }
+
+        let mut constraints = Vec::new();
+        let kinds = [
+            (doc.ik, ConstraintKind::Ik, "ik"),
+            (doc.transform, ConstraintKind::Transform, "transform"),
+            (doc.path, ConstraintKind::Path, "path"),
+        ];
+        for (jconstraints, kind, name) in kinds {
+            for (i, c) in jconstraints.into_iter().flatten().enumerate() {
+                let constraint = Constraint::from_json(c, kind, &bone_names, &slot_names)
+                    .map_err(|e| e.at(&format!("{}[{}]", name, i)))?;
+                constraints.push(constraint);
+            }
+        }
+
+        let (mut animations, mut animation_names) = (Vec::new(), util::Names::new());
+        if !self.skip_animations {
+            for janimations in doc.animations.into_iter() {
+                for (name, animation) in janimations.into_iter() {
+                    let path = format!("animations.{}", name);
+                    shear_lints(&animation, &path, &mut lints);
+                    let animation = Animation::from_json(
+                        name,
+                        animation,
+                        &bone_names,
+                        &slot_names,
+                        scale,
+                        self.curves,
+                    )
+                    .map_err(|e| e.at(&path))?;
+                    util::insert_name(&mut animation_names, &animation.name, animations.len());
+                    animations.push(animation);
+                }
+            }
+        }
+
+        let mut skins = HashMap::new();
+        if !self.skip_skins {
+            for jskin in doc.skins.into_iter() {
+                for (i, jskin) in jskin.into_named().into_iter().enumerate() {
+                    let skin_name = jskin.name;
+                    if skins.contains_key(&skin_name) {
+                        // the first skin is kept, as the first bone or slot of a name
+                        self.duplicate_name(&skin_name, format!("skins[{}]", i), &mut lints)?;
+                        continue;
+                    }
+                    let path = format!("skins.{}", skin_name);
+                    let mut skin = Skin::new();
+                    for name in jskin.bones.into_iter().flat_map(|b| b.into_iter()) {
+                        let bone = util::bone_index(&name, &bone_names)
+                            .map_err(|e| e.at(&format!("{}.bones", path)))?;
+                        skin.bones.push(bone);
+                    }
+                    let kinds = [
+                        (jskin.ik, &mut skin.ik_constraints, ConstraintKind::Ik, "ik"),
+                        (
+                            jskin.transform,
+                            &mut skin.transform_constraints,
+                            ConstraintKind::Transform,
+                            "transform",
+                        ),
+                        (
+                            jskin.path,
+                            &mut skin.path_constraints,
+                            ConstraintKind::Path,
+                            "path",
+                        ),
+                    ];
+                    for (jnames, names, kind, key) in kinds {
+                        for (i, name) in jnames.into_iter().flatten().enumerate() {
+                            if !constraints.iter().any(|c| c.kind == kind && c.name == name) {
+                                let error = SkeletonError::ConstraintNotFound(name);
+                                return Err(error.at(&format!("{}.{}[{}]", path, key, i)));
+                            }
+                            names.push(name);
+                        }
+                    }
+                    let jslots = jskin.attachments.unwrap_or_default();
+                    for (slot_name, jattachments) in jslots.into_iter() {
+                        let slot_path = format!("{}.{}", path, slot_name);
+                        let slot_index = util::slot_index(&slot_name, &slot_names)
+                            .map_err(|e| e.at(&slot_path))?;
+                        for (name, attachment) in jattachments.into_iter() {
+                            let at = |e: SkeletonError| e.at(&format!("{}.{}", slot_path, name));
+                            let mut attachment = match Attachment::from_json(
+                                attachment,
+                                Some(name.clone()),
+                                scale,
+                            ) {
+                                Ok(attachment) => attachment,
+                                Err(SkeletonError::InvalidAttachment(
+                                    AttachmentError::UnknownType(_),
+                                )) if !self.strict => continue,
+                                Err(e) => return Err(at(e)),
+                            };
+                            if let Some(ref mut loader) = self.attachment_loader {
+                                loader
+                                    .load(&skin_name, &slot_name, &mut attachment)
+                                    .map_err(at)?;
+                            }
+                            skin.set_attachment(slot_index, &name, attachment);
+                        }
+                    }
+                    skins.insert(skin_name, skin);
+                }
+            }
+        }
+
+        Ok(Skeleton {
+            header,
+            bones,
+            bone_names,
+            slots,
+            slot_names,
+            constraints,
+            skins,
+            animations,
+            animation_names,
+            lints,
+        })
+    }
+}
+
+/// bone shear timelines, which are not applied, by bone name
+fn shear_lints(animation: &json::Animation, path: &str, lints: &mut Vec<Lint>) {
+    let mut bones = animation
+        .bones
+        .iter()
+        .flat_map(|b| b.iter())
+        .collect::<Vec<_>>();
+    bones.sort_by(|a, b| a.0.cmp(b.0));
+    for (bone, timeline) in bones {
+        let shears = [
+            ("shear", timeline.shear.is_some()),
+            ("shearx", timeline.shearx.is_some()),
+            ("sheary", timeline.sheary.is_some()),
+        ];
+        for &(name, _) in shears.iter().filter(|&&(_, some)| some) {
+            let path = format!("{}.bones.{}.{}", path, bone, name);
+            lints.push(Lint::IgnoredTimeline(path));
+        }
+    }
+}
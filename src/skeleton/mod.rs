@@ -1,7 +1,14 @@
 pub mod animation;
 pub mod attachment;
 pub mod bone;
+pub mod color;
+pub mod constraint;
 pub mod error;
+pub mod handle;
+pub mod header;
+pub mod lint;
+pub mod loader;
+pub mod reduce;
 pub mod skin;
 pub mod slot;
 pub mod srt;
@@ -9,95 +16,57 @@ pub mod timeline;
 pub mod util;
 
 use json;
-use serde_json;
 use std::collections::HashMap;
 use std::io::Read;
 
 // Reexport skeleton modules
 use self::animation::skin::SkinAnimation;
 use self::animation::Animation;
-use self::attachment::Attachment;
 use self::bone::Bone;
+use self::constraint::Constraint;
 use self::error::SkeletonError;
+use self::handle::{AnimationHandle, BoneHandle, SlotHandle};
+use self::header::Header;
+use self::lint::{Lint, LintReport};
+use self::loader::SkeletonLoader;
 use self::skin::Skin;
 use self::slot::Slot;
 
 /// Skeleton data converted from json and loaded into memory
 pub struct Skeleton {
+    /// skeleton section of the document
+    header: Header,
     /// bones for the skeleton, hierarchically ordered
     bones: Vec<Bone>,
+    /// bones indices by name
+    bone_names: util::Names,
     /// slots
     slots: Vec<Slot>,
+    /// slots indices by name
+    slot_names: util::Names,
+    /// ik, transform and path constraints
+    constraints: Vec<Constraint>,
     /// skins : key: skin name, value: slots attachments
     skins: HashMap<String, Skin>,
     /// all the animations
-    animations: HashMap<String, Animation>,
+    animations: Vec<Animation>,
+    /// animations indices by name
+    animation_names: util::Names,
+    /// problems found while loading
+    lints: Vec<Lint>,
 }
 
 impl Skeleton {
     /// Consumes reader (with json data) and returns a skeleton wrapping
-    pub fn from_reader<R: Read>(mut reader: R) -> Result<Skeleton, SkeletonError> {
-        // read and convert as json
-        let document: json::Document = serde_json::from_reader(&mut reader)?;
-
-        // convert to skeleton (consumes document)
-        Skeleton::from_json(document)
-    }
-
-    /// Creates a from_json skeleton
-    /// Consumes json::Document
-    fn from_json(doc: json::Document) -> Result<Skeleton, SkeletonError> {
-        let mut bones = Vec::new();
-        if let Some(jbones) = doc.bones {
-            for b in jbones.into_iter() {
-                let bone = Bone::from_json(b, &bones)?;
-                bones.push(bone);
-            }
-        }
-
-        let mut slots = Vec::new();
-        if let Some(jslots) = doc.slots {
-            for s in jslots.into_iter() {
-                let slot = Slot::from_json(s, &bones)?;
-                slots.push(slot);
-            }
-        }
-
-        let mut animations = HashMap::new();
-        for janimations in doc.animations.into_iter() {
-            for (name, animation) in janimations.into_iter() {
-                let animation = Animation::from_json(animation, &bones, &slots)?;
-                animations.insert(name, animation);
-            }
-        }
-
-        let mut skins = HashMap::new();
-        for jskin in doc.skins.into_iter() {
-            for (name, jslots) in jskin.into_iter() {
-                let mut skin = Vec::new();
-                for (name, attachments) in jslots.into_iter() {
-                    let slot_index = util::slot_index(&name, &slots)?;
-                    let attachments = attachments
-                        .into_iter()
-                        .map(|(name, attachment)| {
-                            (
-                                name.clone(),
-                                Attachment::from_json(attachment, Some(name)).unwrap(),
-                            )
-                        })
-                        .collect();
-                    skin.push((slot_index, attachments));
-                }
-                skins.insert(name, Skin { slots: skin });
-            }
-        }
-
-        Ok(Skeleton {
-            bones,
-            slots,
-            skins,
-            animations,
-        })
+    ///
+    /// Use a `loader::SkeletonLoader` to customize how the skeleton is loaded
+    pub fn from_reader<R: Read>(reader: R) -> Result<Skeleton, SkeletonError> {
+        SkeletonLoader::new().load(reader)
+    }
+
+    /// Gets the skeleton header (version, setup pose bounds, fps ...)
+    pub fn header(&self) -> &Header {
+        &self.header
     }
 
     /// get skin
@@ -107,6 +76,95 @@ impl Skeleton {
             .ok_or_else(|| SkeletonError::SkinNotFound(name.to_owned()))
     }
 
+    /// Registers a skin (e.g. built with `Skin::combine`), returning the skin it replaces
+    pub fn add_skin(&mut self, name: &str, skin: Skin) -> Option<Skin> {
+        self.skins.insert(name.to_owned(), skin)
+    }
+
+    /// Gets all bones, parents always come before their children
+    pub fn bones(&self) -> &[Bone] {
+        &self.bones
+    }
+
+    /// Gets a bone, `None` if the handle comes from another skeleton with more bones
+    pub fn bone(&self, bone: BoneHandle) -> Option<&Bone> {
+        self.bones.get(bone.0)
+    }
+
+    /// Gets the parent of a bone, `None` for the root bone
+    pub fn parent(&self, bone: BoneHandle) -> Option<BoneHandle> {
+        self.bones.get(bone.0)?.parent_index.map(BoneHandle)
+    }
+
+    /// Iterates over the direct children of a bone
+    pub fn children<'a>(&'a self, bone: BoneHandle) -> impl Iterator<Item = BoneHandle> + 'a {
+        self.bones
+            .iter()
+            .enumerate()
+            .skip(bone.0 + 1)
+            .filter(move |&(_, b)| b.parent_index == Some(bone.0))
+            .map(|(i, _)| BoneHandle(i))
+    }
+
+    /// Gets all slots, in setup pose draw order
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    /// Gets a slot, `None` if the handle comes from another skeleton with more slots
+    pub fn slot(&self, slot: SlotHandle) -> Option<&Slot> {
+        self.slots.get(slot.0)
+    }
+
+    /// Gets all ik, transform and path constraints
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Gets all animations
+    pub fn animations(&self) -> &[Animation] {
+        &self.animations
+    }
+
+    /// Gets an animation, `None` if the handle comes from another skeleton with more animations
+    pub fn animation(&self, animation: AnimationHandle) -> Option<&Animation> {
+        self.animations.get(animation.0)
+    }
+
+    /// Finds a bone by name
+    pub fn find_bone(&self, name: &str) -> Option<BoneHandle> {
+        self.bone_names.get(name).map(|&i| BoneHandle(i))
+    }
+
+    /// Finds a slot by name, its index is the one used by skins
+    pub fn find_slot(&self, name: &str) -> Option<SlotHandle> {
+        self.slot_names.get(name).map(|&i| SlotHandle(i))
+    }
+
+    /// Gets the index of a slot, to edit skins
+    pub fn get_slot_index(&self, name: &str) -> Result<usize, SkeletonError> {
+        util::slot_index(name, &self.slot_names)
+    }
+
+    /// Finds an animation by name
+    pub fn find_animation(&self, name: &str) -> Option<AnimationHandle> {
+        self.animation_names.get(name).map(|&i| AnimationHandle(i))
+    }
+
+    /// Checks the skeleton for problems which did not prevent it from loading (keys out of
+    /// order, unused bones, attachment keys missing from all skins, ...)
+    pub fn validate(&self) -> LintReport {
+        lint::validate(self, None)
+    }
+
+    /// Same as `validate`, attachment keys being searched in `skin` and the default skin only,
+    /// as `get_animated_skin` does
+    pub fn validate_skin(&self, skin: &str) -> Result<LintReport, SkeletonError> {
+        let mut skins = vec![self.get_skin(skin)?];
+        skins.extend(self.skins.get("default"));
+        Ok(lint::validate(self, Some(&skins)))
+    }
+
     /// Gets a SkinAnimation which can interpolate slots at a given time
     pub fn get_animated_skin<'a>(
         &'a self,
@@ -123,7 +181,7 @@ impl Skeleton {
 
     /// Returns the list of all animations names in this document.
     pub fn get_animations_names(&self) -> Vec<&str> {
-        self.animations.keys().map(|k| &**k).collect()
+        self.animations.iter().map(|a| &*a.name).collect()
     }
 
     /// Returns the list of all attachment names in all skins in this document.
@@ -134,10 +192,10 @@ impl Skeleton {
             .skins
             .values()
             .flat_map(|skin| {
-                skin.slots.iter().flat_map(|&(_, ref attach)| {
+                skin.slots.iter().flat_map(|attach| {
                     attach
                         .iter()
-                        .map(|(k, v)| v.name().map(|n| &**n).unwrap_or(&*k))
+                        .map(|(k, v)| v.name().map(|n| &**n).unwrap_or(k))
                 })
             })
             .collect();
@@ -0,0 +1,302 @@
+//! Module to drop samples or keys which interpolation recovers within a tolerance
+//!
+//! `simplify_json` rewrites a spine json document with fewer animation keys, e.g. exported from
+//! motion capture with a key on every frame. Everything else in the document is kept, members in
+//! their order, but the output is compact json and numbers are written back from their values
+//! (`2.50` becomes `2.5`).
+
+use json::{self, Layout, TimelineCurve};
+use serde_json::{self, Map, Value};
+use skeleton::color::Color;
+use skeleton::error::SkeletonError;
+use skeleton::header::Version;
+use skeleton::loader;
+use skeleton::timeline::curve::Curve;
+use skeleton::timeline::{CurveEvaluation, CurveTimelines, Interpolate};
+use std::io::{Read, Write};
+
+/// Maximum error allowed when dropping samples, per kind of value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// translations, in pixels
+    pub translation: f32,
+    /// rotations, in degrees
+    pub rotation: f32,
+    /// scales, without unit
+    pub scale: f32,
+    /// color channels, from 0 to 1
+    pub color: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Tolerance {
+        Tolerance {
+            translation: 0.1,
+            rotation: 0.1,
+            scale: 0.001,
+            color: 1.0 / 255.0,
+        }
+    }
+}
+
+/// Indices of the samples to keep, the first and last ones included
+///
+/// `fits(from, to)` checks if the samples between `from` and `to` are recovered by
+/// interpolating from sample `from` to sample `to`. Samples are dropped greedily, each kept
+/// sample covering as many following samples as possible.
+pub(crate) fn reduce<F: FnMut(usize, usize) -> bool>(len: usize, mut fits: F) -> Vec<usize> {
+    if len <= 2 {
+        return (0..len).collect();
+    }
+    let mut kept = vec![0];
+    let mut start = 0;
+    while start < len - 1 {
+        let mut end = start + 2;
+        while end < len && fits(start, end) {
+            end += 1;
+        }
+        start = end - 1;
+        kept.push(start);
+    }
+    kept
+}
+
+/// linear interpolation of `from` and `to` at `time`, `from` and `to` being at times 0 and 1
+pub(crate) fn lerp(from: f32, to: f32, time: f32) -> f32 {
+    from + (to - from) * time
+}
+
+/// Reads a json document, removes the animation keys recovered within `tolerance` by
+/// interpolating the kept ones (linearly or with fitted bezier curves if `fit` is set) and
+/// writes the simplified document
+///
+/// Bone timelines and slot color timelines are simplified, in the layout of the document version.
+/// Returns the number of removed keys.
+pub fn simplify_json<R: Read, W: Write>(
+    mut reader: R,
+    writer: W,
+    tolerance: &Tolerance,
+    fit: Option<CurveEvaluation>,
+) -> Result<usize, SkeletonError> {
+    let mut source = Vec::new();
+    reader
+        .read_to_end(&mut source)
+        .map_err(serde_json::Error::io)?;
+    // members are edited in a sorted `Value`, then written back in their order
+    let layout: Layout = serde_json::from_slice(&source)?;
+    let mut document: Value = serde_json::from_slice(&source)?;
+    let mut doc: json::Document = serde_path_to_error::deserialize(document.clone())?;
+    let version = loader::upgrade(&mut doc)?.version;
+    let simplify = Simplify {
+        fit,
+        version: version.as_ref(),
+    };
+
+    let mut removed = 0;
+    for (name, animation) in doc.animations.into_iter().flatten() {
+        let janimation = &mut document["animations"][&name];
+        let path = format!("animations.{}", name);
+        for (bone, timeline) in animation.bones.into_iter().flatten() {
+            removed += simplify
+                .bone(timeline, &mut janimation["bones"][&bone], tolerance)
+                .map_err(|e| e.at(&format!("{}.bones.{}", path, bone)))?;
+        }
+        for (slot, timeline) in animation.slots.into_iter().flatten() {
+            removed += simplify
+                .slot(timeline, &mut janimation["slots"][&slot], tolerance)
+                .map_err(|e| e.at(&format!("{}.slots.{}", path, slot)))?;
+        }
+    }
+
+    serde_json::to_writer(writer, &layout.apply(&document))?;
+    Ok(removed)
+}
+
+/// Simplification of the keys of a document
+struct Simplify<'a> {
+    fit: Option<CurveEvaluation>,
+    version: Option<&'a Version>,
+}
+
+impl<'a> Simplify<'a> {
+    /// simplifies all the timelines of a bone, shears using the rotation tolerance
+    fn bone(
+        &self,
+        t: json::BoneTimeline,
+        jtimeline: &mut Value,
+        tolerance: &Tolerance,
+    ) -> Result<usize, SkeletonError> {
+        let (translation, rotation, scale) =
+            (tolerance.translation, tolerance.rotation, tolerance.scale);
+        Ok(self.keys(t.translate, jtimeline, "translate", translation)?
+            + self.keys(t.translatex, jtimeline, "translatex", translation)?
+            + self.keys(t.translatey, jtimeline, "translatey", translation)?
+            + self.keys(t.rotate, jtimeline, "rotate", rotation)?
+            + self.keys(t.scale, jtimeline, "scale", scale)?
+            + self.keys(t.scalex, jtimeline, "scalex", scale)?
+            + self.keys(t.scaley, jtimeline, "scaley", scale)?
+            + self.keys(t.shear, jtimeline, "shear", rotation)?
+            + self.keys(t.shearx, jtimeline, "shearx", rotation)?
+            + self.keys(t.sheary, jtimeline, "sheary", rotation)?)
+    }
+
+    /// simplifies the color timelines of a slot
+    ///
+    /// 4.x `rgb` and `alpha` timelines, converted to color timelines on loading, are kept as is.
+    fn slot(
+        &self,
+        t: json::SlotTimeline,
+        jtimeline: &mut Value,
+        tolerance: &Tolerance,
+    ) -> Result<usize, SkeletonError> {
+        let find = |names: &[&'static str], jtimeline: &Value| {
+            names.iter().cloned().find(|n| jtimeline.get(n).is_some())
+        };
+        let mut removed = 0;
+        if let Some(name) = find(&["color", "rgba"], jtimeline) {
+            removed += self.keys(t.color, jtimeline, name, tolerance.color)?;
+        }
+        if let Some(name) = find(&["twoColor", "rgba2"], jtimeline) {
+            removed += self.keys(t.two_color, jtimeline, name, tolerance.color)?;
+        }
+        Ok(removed)
+    }
+
+    /// simplifies the keys of timeline `name`, `keys` being the upgraded keys of `jtimeline[name]`
+    fn keys<T, U>(
+        &self,
+        keys: Option<Vec<U>>,
+        jtimeline: &mut Value,
+        name: &str,
+        tolerance: f32,
+    ) -> Result<usize, SkeletonError>
+    where
+        T: Interpolate + Clone,
+        U: Curve<T>,
+    {
+        let jkeys = match jtimeline.get_mut(name) {
+            Some(&mut Value::Array(ref mut jkeys)) => jkeys,
+            _ => return Ok(0),
+        };
+        let timelines = CurveTimelines::from_json_vec(keys, self.fit.unwrap_or_default())
+            .map_err(|e| e.at(name))?;
+        let kept = timelines.simplified_keys(tolerance, self.fit);
+        // keys converted on loading do not match the document keys
+        if kept.last().is_none_or(|k| k.0 + 1 != jkeys.len()) {
+            return Ok(0);
+        }
+
+        let removed = jkeys.len() - kept.len();
+        let mut simplified = Vec::with_capacity(kept.len());
+        for (i, &(index, ref curve)) in kept.iter().enumerate() {
+            let mut key = jkeys[index].clone();
+            if let (Some(curve), Some(next), Some(map)) = (
+                curve.as_ref(),
+                kept.get(i + 1).and_then(|k| jkeys[k.0].as_object()),
+                key.as_object_mut(),
+            ) {
+                self.write_curve(map, curve, next, name);
+            }
+            simplified.push(key);
+        }
+        *jkeys = simplified;
+        Ok(removed)
+    }
+
+    /// replaces the curve of `key` to `next` in the layout of the document version
+    fn write_curve(
+        &self,
+        key: &mut Map<String, Value>,
+        curve: &TimelineCurve,
+        next: &Map<String, Value>,
+        name: &str,
+    ) {
+        for property in &["curve", "c2", "c3", "c4"] {
+            key.remove(*property);
+        }
+        // linear is the default curve, simplified keys are never stepped
+        let points = match *curve {
+            TimelineCurve::CurveBezier(ref points) if points.len() == 4 => points,
+            _ => return,
+        };
+        match self.version {
+            // absolute times and values, for each channel
+            Some(&Version { major: 4, .. }) => {
+                let time = |key: &Map<String, Value>| {
+                    key.get("time").and_then(Value::as_f64).unwrap_or(0.0) as f32
+                };
+                let (t0, dt) = (time(key), time(next) - time(key));
+                let absolute = channels(key, name)
+                    .iter()
+                    .zip(channels(next, name))
+                    .flat_map(|(v0, v1)| {
+                        let dv = v1 - v0;
+                        vec![
+                            t0 + points[0] * dt,
+                            v0 + points[1] * dv,
+                            t0 + points[2] * dt,
+                            v0 + points[3] * dv,
+                        ]
+                    })
+                    .map(number)
+                    .collect::<Vec<_>>();
+                key.insert("curve".to_owned(), Value::Array(absolute));
+            }
+            // first control value in `curve`, the others in `c2`, `c3` and `c4`
+            Some(&Version {
+                major: 3, minor: 8, ..
+            }) => {
+                for (property, &point) in ["curve", "c2", "c3", "c4"].iter().zip(points.iter()) {
+                    key.insert((*property).to_owned(), number(point));
+                }
+            }
+            _ => {
+                let points = points.iter().map(|&p| number(p)).collect();
+                key.insert("curve".to_owned(), Value::Array(points));
+            }
+        }
+    }
+}
+
+/// values of all the channels of a key of timeline `name`, as written in the document
+fn channels(key: &Map<String, Value>, name: &str) -> Vec<f32> {
+    let get = |property: &str, default: f32| {
+        key.get(property)
+            .and_then(Value::as_f64)
+            .map_or(default, |v| v as f32)
+    };
+    let color = |property: &str, rgb: bool| {
+        let hex = key.get(property).and_then(Value::as_str);
+        let color = match hex {
+            Some(hex) if rgb => Color::from_rgb_hex(hex).ok(),
+            Some(hex) => Color::from_rgba_hex(hex).ok(),
+            None => None,
+        }
+        .unwrap_or(Color::WHITE);
+        (0..4).map(|c| color.channel(c)).collect::<Vec<_>>()
+    };
+    match name {
+        // spine 4.x stores rotations in `value`
+        "rotate" if key.contains_key("value") => vec![get("value", 0.0)],
+        "rotate" => vec![get("angle", 0.0)],
+        "translate" | "shear" => vec![get("x", 0.0), get("y", 0.0)],
+        "scale" => vec![get("x", 1.0), get("y", 1.0)],
+        "scalex" | "scaley" => vec![get("value", 1.0)],
+        "color" | "rgba" => color("color", false),
+        "twoColor" | "rgba2" => {
+            let mut channels = color("light", false);
+            channels.extend_from_slice(&color("dark", true)[..3]);
+            channels
+        }
+        _ => vec![get("value", 0.0)],
+    }
+}
+
+/// json number of a f32, without the noise of its conversion to f64
+fn number(value: f32) -> Value {
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .map_or(Value::Null, Value::from)
+}
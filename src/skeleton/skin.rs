@@ -3,32 +3,122 @@ use std::collections::HashMap;
 
 /// Skin
 /// defines a set of slot with custom attachments
-/// slots: Vec<(slot_index, HashMap<custom_attachment_name, Attachment>)>
-/// TODO: simpler architecture
+/// slots: Vec<HashMap<custom_attachment_name, Attachment>>, indexed by slot index
+///
+/// Skins can be built or combined at runtime, then registered with `Skeleton::add_skin`
+#[derive(Debug, Clone, Default)]
 pub struct Skin {
     /// all slots modified by the skin, the default skin contains all skeleton bones
-    pub slots: Vec<(usize, HashMap<String, Attachment>)>,
+    pub slots: Vec<HashMap<String, Attachment>>,
+    /// indices of the bones only active when the skin is applied
+    pub bones: Vec<usize>,
+    /// names of the ik constraints only active when the skin is applied
+    pub ik_constraints: Vec<String>,
+    /// names of the transform constraints only active when the skin is applied
+    pub transform_constraints: Vec<String>,
+    /// names of the path constraints only active when the skin is applied
+    pub path_constraints: Vec<String>,
 }
 
 impl Skin {
+    /// creates an empty skin
+    pub fn new() -> Skin {
+        Skin::default()
+    }
+
+    /// combines several skins, attachments of the last skins replacing the first ones
+    pub fn combine(skins: &[&Skin]) -> Skin {
+        let mut combined = Skin::new();
+        for skin in skins {
+            combined.add_skin(skin);
+        }
+        combined
+    }
+
+    /// checks if a skin-required bone is active with this skin
+    pub fn has_bone(&self, bone_index: usize) -> bool {
+        self.bones.contains(&bone_index)
+    }
+
     /// find attachment in a skin
     pub fn find(&self, slot_index: usize, attach_name: &str) -> Option<&Attachment> {
+        self.slots
+            .get(slot_index)
+            .and_then(|attachs| attachs.get(attach_name))
+    }
+
+    /// attachments of the slots having some, by name, with their slot index
+    pub fn slot_attachments(&self) -> impl Iterator<Item = (usize, &HashMap<String, Attachment>)> {
         self.slots
             .iter()
-            .filter_map(|&(i, ref attachs)| {
-                if i == slot_index {
-                    attachs.get(attach_name)
-                } else {
-                    None
-                }
-            })
-            .next()
+            .enumerate()
+            .filter(|&(_, attachs)| !attachs.is_empty())
     }
 
     pub fn attachments(&self) -> Vec<&Attachment> {
         self.slots
             .iter()
-            .flat_map(|(_, attachs)| attachs.iter().map(|(_, attach)| attach))
+            .flat_map(|attachs| attachs.values())
             .collect()
     }
+
+    /// adds an attachment, returning the one it replaces
+    pub fn set_attachment(
+        &mut self,
+        slot_index: usize,
+        attach_name: &str,
+        attachment: Attachment,
+    ) -> Option<Attachment> {
+        if self.slots.len() <= slot_index {
+            self.slots.resize_with(slot_index + 1, HashMap::new);
+        }
+        self.slots[slot_index].insert(attach_name.to_owned(), attachment)
+    }
+
+    /// removes an attachment
+    pub fn remove_attachment(
+        &mut self,
+        slot_index: usize,
+        attach_name: &str,
+    ) -> Option<Attachment> {
+        self.slots.get_mut(slot_index)?.remove(attach_name)
+    }
+
+    /// copies one attachment of `skin`, returns `false` if `skin` does not have it
+    pub fn copy_attachment(&mut self, skin: &Skin, slot_index: usize, attach_name: &str) -> bool {
+        match skin.find(slot_index, attach_name) {
+            Some(attachment) => {
+                self.set_attachment(slot_index, attach_name, attachment.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// copies all attachments, bones and constraints of `skin`, replacing the existing
+    /// attachments
+    pub fn add_skin(&mut self, skin: &Skin) {
+        for &bone in &skin.bones {
+            if !self.bones.contains(&bone) {
+                self.bones.push(bone);
+            }
+        }
+        let constraints = [
+            (&mut self.ik_constraints, &skin.ik_constraints),
+            (&mut self.transform_constraints, &skin.transform_constraints),
+            (&mut self.path_constraints, &skin.path_constraints),
+        ];
+        for (names, added) in constraints {
+            for name in added {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        for (slot_index, attachs) in skin.slots.iter().enumerate() {
+            for (name, attachment) in attachs {
+                self.set_attachment(slot_index, name, attachment.clone());
+            }
+        }
+    }
 }
@@ -1,36 +1,58 @@
-use super::bone::Bone;
+use super::color::Color;
 use super::json;
 use super::util;
 use super::SkeletonError;
-use rustc_hex::{FromHex, FromHexError};
 
 /// skeleton slot
 pub struct Slot {
     pub name: String,
     pub bone_index: usize,
-    pub color: [u8; 4],
+    pub color: Color,
+    /// dark color used for two color tinting (alpha is unused)
+    pub dark_color: Option<Color>,
     pub attachment: Option<String>,
+    pub blend_mode: BlendMode,
 }
 
 impl Slot {
-    pub fn from_json(slot: json::Slot, bones: &[Bone]) -> Result<Slot, SkeletonError> {
-        let bone_index = util::bone_index(&slot.bone, &bones)?;
+    pub fn from_json(slot: json::Slot, bones: &util::Names) -> Result<Slot, SkeletonError> {
+        let bone_index = util::bone_index(&slot.bone, bones).map_err(|e| e.at("bone"))?;
         let color = match slot.color {
-            Some(c) => {
-                let v = c.from_hex()?;
-                if v.len() != 4 {
-                    return Err(SkeletonError::InvalidColor(FromHexError::InvalidHexLength));
-                }
-                [v[0], v[1], v[2], v[3]]
-            }
-            None => [255, 255, 255, 255],
+            Some(ref c) => Color::from_rgba_hex(c).map_err(|e| e.at("color"))?,
+            None => Color::WHITE,
+        };
+        let dark_color = match slot.dark {
+            Some(ref c) => Some(Color::from_rgb_hex(c).map_err(|e| e.at("dark"))?),
+            None => None,
         };
 
         Ok(Slot {
             name: slot.name,
             bone_index,
             color,
+            dark_color,
             attachment: slot.attachment,
+            blend_mode: slot.blend.map_or(BlendMode::Normal, BlendMode::from),
         })
     }
 }
+
+/// Blending of the slot attachments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Additive,
+    Multiply,
+    Screen,
+}
+
+impl From<String> for BlendMode {
+    fn from(mode: String) -> BlendMode {
+        match &*mode {
+            "additive" => BlendMode::Additive,
+            "multiply" => BlendMode::Multiply,
+            "screen" => BlendMode::Screen,
+            _ => BlendMode::Normal,
+        }
+    }
+}
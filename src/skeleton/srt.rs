@@ -15,9 +15,18 @@ pub struct SRT {
     pub sin: f32,
 }
 
-const TO_RADIAN: f32 = PI / 180f32;
+pub(crate) const TO_RADIAN: f32 = PI / 180f32;
 
 impl SRT {
+    /// identity transformation
+    pub const IDENTITY: SRT = SRT {
+        scale: [1.0, 1.0],
+        rotation: 0.0,
+        position: [0.0, 0.0],
+        cos: 1.0,
+        sin: 0.0,
+    };
+
     /// new srt
     pub fn new(scale_x: f32, scale_y: f32, rotation_deg: f32, x: f32, y: f32) -> SRT {
         let rotation = rotation_deg * TO_RADIAN;
@@ -42,7 +51,7 @@ impl SRT {
     pub fn to_matrix3(&self) -> [[f32; 3]; 3] {
         [
             [self.cos * self.scale[0], self.sin * self.scale[0], 0.0],
-            [-self.sin * self.scale[0], self.cos * self.scale[1], 0.0],
+            [-self.sin * self.scale[1], self.cos * self.scale[1], 0.0],
             [self.position[0], self.position[1], 1.0f32],
         ]
     }
@@ -50,8 +59,13 @@ impl SRT {
     /// convert srt to a 4x4 transformation matrix (3D)
     pub fn to_matrix4(&self) -> [[f32; 4]; 4] {
         [
-            [self.cos * self.scale[0], self.sin, 0.0, 0.0],
-            [-self.sin, self.cos * self.scale[1], 0.0, 0.0],
+            [self.cos * self.scale[0], self.sin * self.scale[0], 0.0, 0.0],
+            [
+                -self.sin * self.scale[1],
+                self.cos * self.scale[1],
+                0.0,
+                0.0,
+            ],
             [0.0, 0.0, 1.0, 0.0],
             [self.position[0], self.position[1], 0.0, 1.0f32],
         ]
@@ -0,0 +1,146 @@
+//! Module to evaluate bezier curves between two keyframes
+
+const BEZIER_SEGMENTS: usize = 10;
+
+/// Precision of the exact solver, on the time axis
+const EPSILON: f32 = 1e-6;
+
+/// How bezier curves are evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurveEvaluation {
+    /// linear interpolation between 10 precomputed points (fast, as the official runtimes)
+    #[default]
+    Segments,
+    /// exact evaluation of the cubic curve
+    Exact,
+}
+
+/// Bezier curve going from (0, 0) to (1, 1)
+///
+/// cx1 and cx2 are from 0 to 1, representing the percent of time between the two keyframes.
+/// cy1 and cy2 are the percent of the difference between the keyframe's values.
+#[derive(Debug, Clone)]
+pub enum Bezier {
+    /// precomputed (x, y) points
+    Segments(Vec<f32>, Vec<f32>),
+    /// control points (cx1, cy1, cx2, cy2)
+    Exact(f32, f32, f32, f32),
+}
+
+impl Bezier {
+    /// Creates a new curve from its control points
+    pub fn new(cx1: f32, cy1: f32, cx2: f32, cy2: f32, evaluation: CurveEvaluation) -> Bezier {
+        match evaluation {
+            CurveEvaluation::Segments => Bezier::segments(cx1, cy1, cx2, cy2),
+            CurveEvaluation::Exact => Bezier::Exact(cx1, cy1, cx2, cy2),
+        }
+    }
+
+    /// Precomputes `BEZIER_SEGMENTS` points of the curve using forward differencing
+    fn segments(cx1: f32, cy1: f32, cx2: f32, cy2: f32) -> Bezier {
+        let subdiv1 = 1f32 / BEZIER_SEGMENTS as f32;
+        let subdiv2 = subdiv1 * subdiv1;
+        let subdiv3 = subdiv2 * subdiv1;
+        let (pre1, pre2, pre4, pre5) = (
+            3f32 * subdiv1,
+            3f32 * subdiv2,
+            6f32 * subdiv2,
+            6f32 * subdiv3,
+        );
+        let (tmp1x, tmp1y) = (-cx1 * 2f32 + cx2, -cy1 * 2f32 + cy2);
+        let (tmp2x, tmp2y) = ((cx1 - cx2) * 3f32 + 1f32, (cy1 - cy2) * 3f32 + 1f32);
+        let mut dfx = cx1 * pre1 + tmp1x * pre2 + tmp2x * subdiv3;
+        let mut dfy = cy1 * pre1 + tmp1y * pre2 + tmp2y * subdiv3;
+        let (mut ddfx, mut ddfy) = (tmp1x * pre4 + tmp2x * pre5, tmp1y * pre4 + tmp2y * pre5);
+        let (dddfx, dddfy) = (tmp2x * pre5, tmp2y * pre5);
+
+        let (mut vec_x, mut vec_y) = (
+            Vec::with_capacity(BEZIER_SEGMENTS),
+            Vec::with_capacity(BEZIER_SEGMENTS),
+        );
+        let (mut x, mut y) = (dfx, dfy);
+        for _ in 0..BEZIER_SEGMENTS {
+            vec_x.push(x);
+            vec_y.push(y);
+            dfx += ddfx;
+            dfy += ddfy;
+            ddfx += dddfx;
+            ddfy += dddfy;
+            x += dfx;
+            y += dfy;
+        }
+        Bezier::Segments(vec_x, vec_y)
+    }
+
+    /// Converts a percent of time into a percent of value
+    pub fn percent(&self, percent: f32) -> f32 {
+        match *self {
+            Bezier::Segments(ref x, ref y) => match x.iter().position(|&xi| percent < xi) {
+                Some(0) => y[0] * percent / x[0],
+                Some(i) => y[i - 1] + (y[i] - y[i - 1]) * (percent - x[i - 1]) / (x[i] - x[i - 1]),
+                None => {
+                    let (x, y) = (x[BEZIER_SEGMENTS - 1], y[BEZIER_SEGMENTS - 1]);
+                    if 1f32 - x <= EPSILON {
+                        y
+                    } else {
+                        y + (1f32 - y) * (percent - x) / (1f32 - x)
+                    }
+                }
+            },
+            Bezier::Exact(cx1, cy1, cx2, cy2) => {
+                let t = solve(percent, cx1, cx2);
+                sample(t, cy1, cy2)
+            }
+        }
+    }
+}
+
+/// Value of a cubic bezier going from 0 to 1 with control values c1 and c2 at t
+pub(crate) fn sample(t: f32, c1: f32, c2: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * c1 + 3.0 * u * t * t * c2 + t * t * t
+}
+
+/// Derivative of `sample` at t
+fn sample_derivative(t: f32, c1: f32, c2: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * c1 + 6.0 * u * t * (c2 - c1) + 3.0 * t * t * (1.0 - c2)
+}
+
+/// Finds t such as `sample(t, c1, c2) == x` (Newton's method, falling back to bisection)
+pub(crate) fn solve(x: f32, c1: f32, c2: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let mut t = x;
+    for _ in 0..8 {
+        let error = sample(t, c1, c2) - x;
+        if error.abs() < EPSILON {
+            return t;
+        }
+        let derivative = sample_derivative(t, c1, c2);
+        if derivative.abs() < EPSILON {
+            break;
+        }
+        t -= error / derivative;
+        if !(0.0..=1.0).contains(&t) {
+            break;
+        }
+    }
+
+    let (mut low, mut high) = (0f32, 1f32);
+    t = x;
+    while high - low > EPSILON {
+        if sample(t, c1, c2) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) * 0.5;
+    }
+    t
+}
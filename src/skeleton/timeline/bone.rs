@@ -1,33 +1,195 @@
-use super::CurveTimelines;
+use super::{Cursor, CurveEvaluation, CurveTimelines};
 use json;
-use skeleton::{error::SkeletonError, srt::SRT};
+use skeleton::srt::{SRT, TO_RADIAN};
+use skeleton::{error::SkeletonError, lint::Lint, reduce::Tolerance};
 
 pub struct BoneTimeline {
     translate: CurveTimelines<(f32, f32)>,
+    translate_x: CurveTimelines<f32>,
+    translate_y: CurveTimelines<f32>,
     rotate: CurveTimelines<f32>,
     scale: CurveTimelines<(f32, f32)>,
+    scale_x: CurveTimelines<f32>,
+    scale_y: CurveTimelines<f32>,
+}
+
+/// scales single value keys
+fn scale_values(keys: &mut Option<Vec<json::ValueTimeline>>, scale: f32) {
+    for key in keys.iter_mut().flat_map(|k| k.iter_mut()) {
+        key.value = key.value.map(|v| v * scale);
+    }
 }
 
 impl BoneTimeline {
     /// converts json data into BoneTimeline
-    pub fn from_json(json: json::BoneTimeline) -> Result<BoneTimeline, SkeletonError> {
-        let translate = CurveTimelines::from_json_vec(json.translate)?;
-        let rotate = CurveTimelines::from_json_vec(json.rotate)?;
-        let scale = CurveTimelines::from_json_vec(json.scale)?;
+    pub fn from_json(
+        mut json: json::BoneTimeline,
+        scale: f32,
+        curves: CurveEvaluation,
+    ) -> Result<BoneTimeline, SkeletonError> {
+        if scale != 1.0 {
+            for t in json.translate.iter_mut().flat_map(|t| t.iter_mut()) {
+                t.x = t.x.map(|x| x * scale);
+                t.y = t.y.map(|y| y * scale);
+            }
+            scale_values(&mut json.translatex, scale);
+            scale_values(&mut json.translatey, scale);
+        }
+
+        let at = |name: &'static str| move |e: SkeletonError| e.at(name);
         Ok(BoneTimeline {
-            translate,
-            rotate,
-            scale,
+            translate: CurveTimelines::from_json_vec(json.translate, curves)
+                .map_err(at("translate"))?,
+            translate_x: CurveTimelines::from_json_vec(json.translatex, curves)
+                .map_err(at("translatex"))?,
+            translate_y: CurveTimelines::from_json_vec(json.translatey, curves)
+                .map_err(at("translatey"))?,
+            rotate: CurveTimelines::from_json_vec(json.rotate, curves).map_err(at("rotate"))?,
+            scale: CurveTimelines::from_json_vec(json.scale, curves).map_err(at("scale"))?,
+            scale_x: CurveTimelines::from_json_vec(json.scalex, curves).map_err(at("scalex"))?,
+            scale_y: CurveTimelines::from_json_vec(json.scaley, curves).map_err(at("scaley"))?,
         })
     }
 
+    /// number of cursors used by `srt_with_cursors`
+    pub const CURSORS: usize = 7;
+
     /// evaluates the interpolations for elapsed time on all timelines and
     /// returns the corresponding srt
+    ///
+    /// Single axis timelines (e.g. `translatex`) override the matching axis of combined ones
     pub fn srt(&self, elapsed: f32) -> SRT {
-        let (x, y) = self.translate.interpolate(elapsed).unwrap_or((0f32, 0f32));
-        let rotation = self.rotate.interpolate(elapsed).unwrap_or(0f32);
-        let (scale_x, scale_y) = self.scale.interpolate(elapsed).unwrap_or((1.0, 1.0));
+        self.srt_with_cursors(elapsed, &mut [Cursor::new(); BoneTimeline::CURSORS])
+    }
+
+    /// same as `srt`, with a cursor per timeline to speed up playback moving forward
+    pub fn srt_with_cursors(
+        &self,
+        elapsed: f32,
+        cursors: &mut [Cursor; BoneTimeline::CURSORS],
+    ) -> SRT {
+        let (mut x, mut y) = self
+            .translate
+            .interpolate_with_cursor(elapsed, &mut cursors[0])
+            .unwrap_or((0f32, 0f32));
+        x = self
+            .translate_x
+            .interpolate_with_cursor(elapsed, &mut cursors[1])
+            .unwrap_or(x);
+        y = self
+            .translate_y
+            .interpolate_with_cursor(elapsed, &mut cursors[2])
+            .unwrap_or(y);
+        let rotation = self
+            .rotate
+            .interpolate_with_cursor(elapsed, &mut cursors[3])
+            .unwrap_or(0f32);
+        let (mut scale_x, mut scale_y) = self
+            .scale
+            .interpolate_with_cursor(elapsed, &mut cursors[4])
+            .unwrap_or((1.0, 1.0));
+        scale_x = self
+            .scale_x
+            .interpolate_with_cursor(elapsed, &mut cursors[5])
+            .unwrap_or(scale_x);
+        scale_y = self
+            .scale_y
+            .interpolate_with_cursor(elapsed, &mut cursors[6])
+            .unwrap_or(scale_y);
 
         SRT::new(scale_x, scale_y, rotation, x, y)
     }
+
+    /// evaluates the timelines for each of `times` as `srt` would, one timeline at a time
+    ///
+    /// Writes translations, rotations (in radians) and scales, slices are indexed as `times`
+    pub fn srt_batch(
+        &self,
+        times: &[f32],
+        translation: (&mut [f32], &mut [f32]),
+        rotation: &mut [f32],
+        scale: (&mut [f32], &mut [f32]),
+    ) {
+        let ((x, y), (scale_x, scale_y)) = (translation, scale);
+        for v in x.iter_mut().chain(y.iter_mut()).chain(rotation.iter_mut()) {
+            *v = 0.0;
+        }
+        for v in scale_x.iter_mut().chain(scale_y.iter_mut()) {
+            *v = 1.0;
+        }
+        self.translate.interpolate_batch(times, |i, (tx, ty)| {
+            x[i] = tx;
+            y[i] = ty;
+        });
+        self.translate_x.interpolate_batch(times, |i, v| x[i] = v);
+        self.translate_y.interpolate_batch(times, |i, v| y[i] = v);
+        self.rotate
+            .interpolate_batch(times, |i, v| rotation[i] = v * TO_RADIAN);
+        self.scale.interpolate_batch(times, |i, (sx, sy)| {
+            scale_x[i] = sx;
+            scale_y[i] = sy;
+        });
+        self.scale_x.interpolate_batch(times, |i, v| scale_x[i] = v);
+        self.scale_y.interpolate_batch(times, |i, v| scale_y[i] = v);
+    }
+
+    /// names of the non empty timelines, as in the json document
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            ("translate", self.translate.timelines.is_empty()),
+            ("translatex", self.translate_x.timelines.is_empty()),
+            ("translatey", self.translate_y.timelines.is_empty()),
+            ("rotate", self.rotate.timelines.is_empty()),
+            ("scale", self.scale.timelines.is_empty()),
+            ("scalex", self.scale_x.timelines.is_empty()),
+            ("scaley", self.scale_y.timelines.is_empty()),
+        ]
+        .iter()
+        .filter(|&&(_, empty)| !empty)
+        .map(|&(name, _)| name)
+        .collect()
+    }
+
+    /// removes the keys recovered within `tolerance` by interpolating the kept ones (see
+    /// `CurveTimelines::simplify`)
+    ///
+    /// Returns the number of removed keys
+    pub fn simplify(&mut self, tolerance: &Tolerance, fit: Option<CurveEvaluation>) -> usize {
+        let (translation, rotation, scale) =
+            (tolerance.translation, tolerance.rotation, tolerance.scale);
+        self.translate.simplify(translation, fit)
+            + self.translate_x.simplify(translation, fit)
+            + self.translate_y.simplify(translation, fit)
+            + self.rotate.simplify(rotation, fit)
+            + self.scale.simplify(scale, fit)
+            + self.scale_x.simplify(scale, fit)
+            + self.scale_y.simplify(scale, fit)
+    }
+
+    /// reports invalid keys of all timelines, `path` being the path of the bone timelines
+    pub(crate) fn lint(&self, path: &str, lints: &mut Vec<Lint>) {
+        let at = |name: &str| format!("{}.{}", path, name);
+        self.translate.lint(&at("translate"), lints);
+        self.translate_x.lint(&at("translatex"), lints);
+        self.translate_y.lint(&at("translatey"), lints);
+        self.rotate.lint(&at("rotate"), lints);
+        self.scale.lint(&at("scale"), lints);
+        self.scale_x.lint(&at("scalex"), lints);
+        self.scale_y.lint(&at("scaley"), lints);
+    }
+
+    /// time of the last key of all timelines
+    pub fn duration(&self) -> f32 {
+        [
+            self.translate.duration(),
+            self.translate_x.duration(),
+            self.translate_y.duration(),
+            self.rotate.duration(),
+            self.scale.duration(),
+            self.scale_x.duration(),
+            self.scale_y.duration(),
+        ]
+        .iter()
+        .fold(0f32, |d, &t| d.max(t))
+    }
 }
@@ -1,9 +1,14 @@
+use super::bezier::{Bezier, CurveEvaluation};
 use super::Interpolate;
 use json;
-use rustc_hex::{FromHex, FromHexError};
+use skeleton::color::Color;
 use skeleton::error::SkeletonError;
+use skeleton::lint::Lint;
+use skeleton::reduce;
+use std::mem;
 
-const BEZIER_SEGMENTS: usize = 10;
+/// Number of intervals checked between two keys when simplifying timelines
+const SIMPLIFY_INTERVALS: usize = 8;
 
 /// Curve trait to define struct with curve property (unwrapped to Linear)
 pub trait Curve<T> {
@@ -49,11 +54,8 @@ impl_curve!(
     f32,
     |t: &json::BoneRotateTimeline| {
         let mut angle = t.angle.unwrap_or(0f32);
-        while angle > 180.0 {
-            angle -= 360.0;
-        }
-        while angle < -180.0 {
-            angle += 360.0;
+        if angle.abs() > 180.0 {
+            angle -= (angle / 360.0).round() * 360.0;
         }
         Ok(angle)
     }
@@ -61,21 +63,35 @@ impl_curve!(
 
 impl_curve!(
     json::SlotColorTimeline,
-    [u8; 4],
+    Color,
     |t: &json::SlotColorTimeline| {
         Ok(match t.color {
-            Some(ref c) => {
-                let v = c.from_hex()?;
-                if v.len() != 4 {
-                    return Err(SkeletonError::InvalidColor(FromHexError::InvalidHexLength));
-                }
-                [v[0], v[1], v[2], v[3]]
-            }
-            None => [255, 255, 255, 255],
+            Some(ref c) => Color::from_rgba_hex(c)?,
+            None => Color::WHITE,
         })
     }
 );
 
+impl_curve!(
+    json::SlotTwoColorTimeline,
+    (Color, Color),
+    |t: &json::SlotTwoColorTimeline| {
+        let light = match t.light {
+            Some(ref c) => Color::from_rgba_hex(c)?,
+            None => Color::WHITE,
+        };
+        let dark = match t.dark {
+            Some(ref c) => Color::from_rgb_hex(c)?,
+            None => Color::BLACK,
+        };
+        Ok((light, dark))
+    }
+);
+
+impl_curve!(json::ValueTimeline, f32, |t: &json::ValueTimeline| {
+    Ok(t.value.unwrap_or(0f32))
+});
+
 impl Curve<Option<String>> for json::SlotAttachmentTimeline {
     fn time(&self) -> f32 {
         self.time
@@ -88,74 +104,80 @@ impl Curve<Option<String>> for json::SlotAttachmentTimeline {
     }
 }
 
+/// Position in a timeline, caching the last key found to speed up playback moving forward
+///
+/// A cursor can be used with any timeline, results are the same as without cursor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cursor {
+    count: usize,
+}
+
+impl Cursor {
+    /// cursor before the first key
+    pub fn new() -> Cursor {
+        Cursor::default()
+    }
+
+    /// number of `keys` at or before `elapsed`, `keys` being sorted by time
+    ///
+    /// Checks the keys following the previous position first, then binary searches
+    pub fn seek<K, F: Fn(&K) -> f32>(&mut self, keys: &[K], time: F, elapsed: f32) -> usize {
+        let count = self.count;
+        let forward = count <= keys.len() && (count == 0 || time(&keys[count - 1]) <= elapsed);
+        self.count = if !forward {
+            keys_before(keys, time, elapsed)
+        } else if keys.get(count).is_none_or(|k| elapsed < time(k)) {
+            count
+        } else if keys.get(count + 1).is_none_or(|k| elapsed < time(k)) {
+            count + 1
+        } else {
+            count + 1 + keys_before(&keys[count + 1..], time, elapsed)
+        };
+        self.count
+    }
+}
+
+/// number of `keys` at or before `elapsed`, `keys` being sorted by time
+pub(crate) fn keys_before<K, F: Fn(&K) -> f32>(keys: &[K], time: F, elapsed: f32) -> usize {
+    keys.partition_point(|k| time(k) <= elapsed)
+}
+
 pub struct CurveTimeline<T> {
     pub time: f32,
     pub curve: json::TimelineCurve,
-    pub points: Option<(Vec<f32>, Vec<f32>)>, // bezier curve interpolations points
+    pub points: Vec<Bezier>, // bezier curves, per channel
     pub value: T,
 }
 
-impl<T> CurveTimeline<T> {
-    /// interpolation values (x, y)
-    /// Sets the control handle positions for an interpolation bezier curve used to transition
-    /// from this keyframe to the next.
-    /// cx1 and cx2 are from 0 to 1, representing the percent of time between the two keyframes.
-    /// cy1 and cy2 are the percent of the difference between the keyframe's values.
-    pub fn compute_points(curve: &json::TimelineCurve) -> Option<(Vec<f32>, Vec<f32>)> {
-        let (cx1, cy1, cx2, cy2) = match *curve {
-            json::TimelineCurve::CurveStepped | json::TimelineCurve::CurveLinear => return None, // no interpolation: early return
-            json::TimelineCurve::CurveBezier(ref p) => (p[0], p[1], p[2], p[3]),
+impl<T: Interpolate> CurveTimeline<T> {
+    /// bezier curves for each channel
+    ///
+    /// A bezier curve is either 4 values shared by all channels or 4 values per channel
+    pub fn compute_points(
+        curve: &json::TimelineCurve,
+        evaluation: CurveEvaluation,
+    ) -> Result<Vec<Bezier>, SkeletonError> {
+        let p = match *curve {
+            json::TimelineCurve::CurveStepped | json::TimelineCurve::CurveLinear => {
+                return Ok(Vec::new()); // no interpolation: early return
+            }
+            json::TimelineCurve::CurveBezier(ref p) => p,
         };
-
-        let subdiv1 = 1f32 / BEZIER_SEGMENTS as f32;
-        let subdiv2 = subdiv1 * subdiv1;
-        let subdiv3 = subdiv2 * subdiv1;
-        let (pre1, pre2, pre4, pre5) = (
-            3f32 * subdiv1,
-            3f32 * subdiv2,
-            6f32 * subdiv2,
-            6f32 * subdiv3,
-        );
-        let (tmp1x, tmp1y) = (-cx1 * 2f32 + cx2, -cy1 * 2f32 + cy2);
-        let (tmp2x, tmp2y) = ((cx1 - cx2) * 3f32 + 1f32, (cy1 - cy2) * 3f32 + 1f32);
-        let mut dfx = cx1 * pre1 + tmp1x * pre2 + tmp2x * subdiv3;
-        let mut dfy = cy1 * pre1 + tmp1y * pre2 + tmp2y * subdiv3;
-        let (mut ddfx, mut ddfy) = (tmp1x * pre4 + tmp2x * pre5, tmp1y * pre4 + tmp2y * pre5);
-        let (dddfx, dddfy) = (tmp2x * pre5, tmp2y * pre5);
-
-        let (mut vec_x, mut vec_y) = (
-            Vec::with_capacity(BEZIER_SEGMENTS),
-            Vec::with_capacity(BEZIER_SEGMENTS),
-        );
-        let (mut x, mut y) = (dfx, dfy);
-        for _ in 0..BEZIER_SEGMENTS {
-            vec_x.push(x);
-            vec_y.push(y);
-            dfx += ddfx;
-            dfy += ddfy;
-            ddfx += dddfx;
-            ddfy += dddfy;
-            x += dfx;
-            y += dfy;
+        if p.len() != 4 && p.len() != 4 * T::CHANNELS {
+            return Err(SkeletonError::InvalidCurve(p.len()));
         }
-        Some((vec_x, vec_y))
+        Ok(p.chunks(4)
+            .map(|c| Bezier::new(c[0], c[1], c[2], c[3], evaluation))
+            .collect())
     }
 
-    /// Get percent conversion depending on curve type
-    pub fn get_percent(&self, percent: f32) -> f32 {
-        let &(ref x, ref y) = match self.curve {
-            json::TimelineCurve::CurveStepped => return 0f32,
-            json::TimelineCurve::CurveLinear => return percent,
-            json::TimelineCurve::CurveBezier(..) => self.points.as_ref().unwrap(),
-        };
-
-        // bezier curve
-        match x.iter().position(|&xi| percent < xi) {
-            Some(0) => y[0] * percent / x[0],
-            Some(i) => y[i] + (y[i] - y[i - 1]) * (percent - x[i - 1]) / (x[i] - x[i - 1]),
-            None => {
-                let (x, y) = (x[BEZIER_SEGMENTS - 1], y[BEZIER_SEGMENTS - 1]);
-                y + (1f32 - y) * (percent - x) / (1f32 - x)
+    /// Get percent conversion of `channel` depending on curve type
+    pub fn get_percent(&self, channel: usize, percent: f32) -> f32 {
+        match self.curve {
+            json::TimelineCurve::CurveStepped => 0f32,
+            json::TimelineCurve::CurveLinear => percent,
+            json::TimelineCurve::CurveBezier(..) => {
+                self.points[channel.min(self.points.len() - 1)].percent(percent)
             }
         }
     }
@@ -170,6 +192,7 @@ impl<T: Interpolate + Clone> CurveTimelines<T> {
     /// Converts vector of json timelines to vector or timelines
     pub fn from_json_vec<U: Curve<T>>(
         jtimelines: Option<Vec<U>>,
+        evaluation: CurveEvaluation,
     ) -> Result<CurveTimelines<T>, SkeletonError> {
         match jtimelines {
             None => Ok(CurveTimelines {
@@ -177,10 +200,12 @@ impl<T: Interpolate + Clone> CurveTimelines<T> {
             }),
             Some(timelines) => {
                 let mut curves = Vec::with_capacity(timelines.len());
-                for t in timelines.into_iter() {
-                    let value = t.value()?;
+                for (i, t) in timelines.into_iter().enumerate() {
+                    let at = |e: SkeletonError| e.at(&format!("[{}]", i));
+                    let value = t.value().map_err(at)?;
                     let curve = t.curve();
-                    let points = CurveTimeline::<T>::compute_points(&curve);
+                    let points =
+                        CurveTimeline::<T>::compute_points(&curve, evaluation).map_err(at)?;
                     curves.push(CurveTimeline {
                         time: t.time(),
                         curve,
@@ -193,18 +218,201 @@ impl<T: Interpolate + Clone> CurveTimelines<T> {
         }
     }
 
+    /// time of the last key
+    pub fn duration(&self) -> f32 {
+        self.timelines.last().map_or(0f32, |t| t.time)
+    }
+
+    /// interpolates `value` for each of `times`, calling `f` with the time index and the value
+    /// where `interpolate` would not return `None`
+    ///
+    /// Keys are searched with one cursor over all `times`: sorted times only check the keys
+    /// following the previous time, unsorted ones fall back to a binary search
+    pub fn interpolate_batch<F: FnMut(usize, T)>(&self, times: &[f32], mut f: F) {
+        if self.timelines.is_empty() {
+            return;
+        }
+        let mut cursor = Cursor::new();
+        for (i, &time) in times.iter().enumerate() {
+            if let Some(value) = self.interpolate_with_cursor(time, &mut cursor) {
+                f(i, value);
+            }
+        }
+    }
+
+    /// removes the keys recovered within `tolerance` (on every channel) by interpolating the
+    /// kept ones, linearly or with a fitted bezier curve if `fit` is set
+    ///
+    /// Returns the number of removed keys
+    pub fn simplify(&mut self, tolerance: f32, fit: Option<CurveEvaluation>) -> usize {
+        let mut kept = self.simplified_keys(tolerance, fit).into_iter().peekable();
+        let removed = self.timelines.len() - kept.len();
+        for (i, mut key) in mem::take(&mut self.timelines).into_iter().enumerate() {
+            if kept.peek().map(|k| k.0) != Some(i) {
+                continue;
+            }
+            if let Some((_, Some(curve))) = kept.next() {
+                key.points = CurveTimeline::<T>::compute_points(&curve, fit.unwrap_or_default())
+                    .unwrap_or_default();
+                key.curve = curve;
+            }
+            self.timelines.push(key);
+        }
+        removed
+    }
+
+    /// indices of the keys kept by `simplify`, with their new curve if it changes
+    pub(crate) fn simplified_keys(
+        &self,
+        tolerance: f32,
+        fit: Option<CurveEvaluation>,
+    ) -> Vec<(usize, Option<json::TimelineCurve>)> {
+        let keys = &self.timelines;
+
+        // values between each key and the next one
+        let samples = keys
+            .windows(2)
+            .map(|w| {
+                (1..SIMPLIFY_INTERVALS)
+                    .map(|k| {
+                        let percent = k as f32 / SIMPLIFY_INTERVALS as f32;
+                        let time = w[0].time + (w[1].time - w[0].time) * percent;
+                        let value = w[0]
+                            .value
+                            .interpolate(&w[1].value, |c| w[0].get_percent(c, percent));
+                        (time, value)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // keys and values replaced by a curve from key `from` to key `to`
+        let replaced = |from: usize, to: usize| {
+            keys[from + 1..to]
+                .iter()
+                .map(|k| (k.time, &k.value))
+                .chain(samples[from..to].iter().flatten().map(|(t, v)| (*t, v)))
+        };
+
+        let fits = |from: usize, to: usize, curve: &json::TimelineCurve| {
+            let (a, b) = (&keys[from], &keys[to]);
+            if b.time <= a.time
+                || keys[from..to]
+                    .iter()
+                    .any(|k| matches!(k.curve, json::TimelineCurve::CurveStepped))
+            {
+                return false;
+            }
+            let points = match CurveTimeline::<T>::compute_points(curve, fit.unwrap_or_default()) {
+                Ok(points) => points,
+                Err(_) => return false,
+            };
+            let key = CurveTimeline {
+                time: a.time,
+                curve: curve.clone(),
+                points,
+                value: a.value.clone(),
+            };
+            replaced(from, to).all(|(time, value)| {
+                let percent = (time - a.time) / (b.time - a.time);
+                let v = a
+                    .value
+                    .interpolate(&b.value, |c| key.get_percent(c, percent));
+                (0..T::CHANNELS).all(|c| (v.channel(c) - value.channel(c)).abs() <= tolerance)
+            })
+        };
+
+        // bezier curve (1/3, cy1, 2/3, cy2), shared by all channels, minimizing the squared
+        // errors: with these cx, the curve percent is a cubic polynomial of the time percent
+        let fitted = |from: usize, to: usize| {
+            let (a, b) = (&keys[from], &keys[to]);
+            let (mut aa, mut ab, mut bb, mut ar, mut br) = (0f32, 0f32, 0f32, 0f32, 0f32);
+            for (time, value) in replaced(from, to) {
+                let p = (time - a.time) / (b.time - a.time);
+                let (c1, c2) = (3.0 * (1.0 - p) * (1.0 - p) * p, 3.0 * (1.0 - p) * p * p);
+                for c in 0..T::CHANNELS {
+                    let (v0, dv) = (a.value.channel(c), b.value.channel(c) - a.value.channel(c));
+                    // weighted by the value range to minimize errors on values
+                    let r = value.channel(c) - v0 - dv * p * p * p;
+                    aa += dv * dv * c1 * c1;
+                    ab += dv * dv * c1 * c2;
+                    bb += dv * dv * c2 * c2;
+                    ar += dv * c1 * r;
+                    br += dv * c2 * r;
+                }
+            }
+            let det = aa * bb - ab * ab;
+            if det.abs() <= f32::EPSILON * aa * bb {
+                return None;
+            }
+            let (cy1, cy2) = ((ar * bb - br * ab) / det, (aa * br - ab * ar) / det);
+            Some(json::TimelineCurve::CurveBezier(vec![
+                1.0 / 3.0,
+                cy1,
+                2.0 / 3.0,
+                cy2,
+            ]))
+        };
+
+        // curve replacing the keys between `from` and `to`, if any
+        let curve = |from: usize, to: usize| {
+            if fits(from, to, &json::TimelineCurve::CurveLinear) {
+                Some(json::TimelineCurve::CurveLinear)
+            } else if fit.is_some() {
+                fitted(from, to).filter(|curve| fits(from, to, curve))
+            } else {
+                None
+            }
+        };
+
+        let kept = reduce::reduce(keys.len(), |from, to| curve(from, to).is_some());
+        kept.iter()
+            .enumerate()
+            .map(|(i, &from)| match kept.get(i + 1) {
+                Some(&to) if to > from + 1 => (from, curve(from, to)),
+                _ => (from, None),
+            })
+            .collect()
+    }
+
+    /// reports keys out of order and bezier curves to a key at the same time
+    pub(crate) fn lint(&self, path: &str, lints: &mut Vec<Lint>) {
+        for (i, w) in self.timelines.windows(2).enumerate() {
+            if w[1].time < w[0].time {
+                lints.push(Lint::KeysOutOfOrder(format!("{}[{}]", path, i + 1)));
+            } else if w[1].time == w[0].time {
+                if let json::TimelineCurve::CurveBezier(..) = w[0].curve {
+                    lints.push(Lint::ZeroLengthCurve(format!("{}[{}]", path, i)));
+                }
+            }
+        }
+    }
+
     /// interpolates `value` in the interval containing elapsed
     pub fn interpolate(&self, elapsed: f32) -> Option<T> {
-        if self.timelines.is_empty() || elapsed < self.timelines[0].time {
+        self.interpolate_key(keys_before(&self.timelines, |t| t.time, elapsed), elapsed)
+    }
+
+    /// interpolates `value` in the interval containing elapsed, starting the search of the
+    /// interval from `cursor`
+    pub fn interpolate_with_cursor(&self, elapsed: f32, cursor: &mut Cursor) -> Option<T> {
+        self.interpolate_key(cursor.seek(&self.timelines, |t| t.time, elapsed), elapsed)
+    }
+
+    /// interpolates `value` after the key `count - 1`, `count` keys being before elapsed
+    fn interpolate_key(&self, count: usize, elapsed: f32) -> Option<T> {
+        if count == 0 {
             return None;
         }
-
-        if let Some(w) = self.timelines.windows(2).find(|&w| elapsed < w[1].time) {
-            let percent = (elapsed - w[0].time) / (w[1].time - w[0].time);
-            let curve_percent = w[0].get_percent(percent);
-            Some(w[0].value.interpolate(&w[1].value, curve_percent))
-        } else {
-            Some(self.timelines[self.timelines.len() - 1].value.clone())
+        match self.timelines.get(count) {
+            Some(next) => {
+                let key = &self.timelines[count - 1];
+                let percent = (elapsed - key.time) / (next.time - key.time);
+                Some(
+                    key.value
+                        .interpolate(&next.value, |channel| key.get_percent(channel, percent)),
+                )
+            }
+            None => Some(self.timelines[count - 1].value.clone()),
         }
     }
 }
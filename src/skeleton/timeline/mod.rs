@@ -1,37 +1,102 @@
+pub mod bezier;
 pub mod bone;
 pub mod curve;
 pub mod slot;
 
+pub use self::bezier::{Bezier, CurveEvaluation};
 pub use self::bone::BoneTimeline;
-pub use self::curve::{CurveTimeline, CurveTimelines};
+pub use self::curve::{Cursor, CurveTimeline, CurveTimelines};
 pub use self::slot::SlotTimeline;
 
+use skeleton::color::Color;
+
+/// Value which can be interpolated channel by channel
 pub trait Interpolate {
-    fn interpolate(&self, next: &Self, percent: f32) -> Self;
+    /// number of channels (e.g. 2 for x and y)
+    const CHANNELS: usize;
+
+    /// interpolates all channels, `percent` returning the percent of each channel
+    fn interpolate<F: Fn(usize) -> f32>(&self, next: &Self, percent: F) -> Self;
+
+    /// value of a channel
+    fn channel(&self, channel: usize) -> f32;
+}
+
+fn lerp(from: f32, to: f32, percent: f32) -> f32 {
+    from + percent * (to - from)
 }
 
 impl Interpolate for f32 {
-    fn interpolate(&self, next: &Self, percent: f32) -> Self {
-        *self + percent * (*next - *self)
+    const CHANNELS: usize = 1;
+
+    fn interpolate<F: Fn(usize) -> f32>(&self, next: &Self, percent: F) -> Self {
+        lerp(*self, *next, percent(0))
+    }
+
+    fn channel(&self, _channel: usize) -> f32 {
+        *self
     }
 }
 
 impl Interpolate for (f32, f32) {
-    fn interpolate(&self, next: &Self, percent: f32) -> Self {
+    const CHANNELS: usize = 2;
+
+    fn interpolate<F: Fn(usize) -> f32>(&self, next: &Self, percent: F) -> Self {
         (
-            self.0 + percent * (next.0 - self.0),
-            self.1 + percent * (next.1 - self.1),
+            lerp(self.0, next.0, percent(0)),
+            lerp(self.1, next.1, percent(1)),
         )
     }
+
+    fn channel(&self, channel: usize) -> f32 {
+        match channel {
+            0 => self.0,
+            _ => self.1,
+        }
+    }
 }
 
-impl Interpolate for [u8; 4] {
-    fn interpolate(&self, next: &Self, percent: f32) -> Self {
-        [
-            (self[0] as f32).interpolate(&(next[0] as f32), percent) as u8,
-            (self[1] as f32).interpolate(&(next[1] as f32), percent) as u8,
-            (self[2] as f32).interpolate(&(next[2] as f32), percent) as u8,
-            (self[3] as f32).interpolate(&(next[3] as f32), percent) as u8,
-        ]
+impl Interpolate for Color {
+    const CHANNELS: usize = 4;
+
+    fn interpolate<F: Fn(usize) -> f32>(&self, next: &Self, percent: F) -> Self {
+        Color::new(
+            lerp(self.r, next.r, percent(0)),
+            lerp(self.g, next.g, percent(1)),
+            lerp(self.b, next.b, percent(2)),
+            lerp(self.a, next.a, percent(3)),
+        )
+    }
+
+    fn channel(&self, channel: usize) -> f32 {
+        match channel {
+            0 => self.r,
+            1 => self.g,
+            2 => self.b,
+            _ => self.a,
+        }
+    }
+}
+
+/// light and dark colors, dark alpha is not animated
+impl Interpolate for (Color, Color) {
+    const CHANNELS: usize = 7;
+
+    fn interpolate<F: Fn(usize) -> f32>(&self, next: &Self, percent: F) -> Self {
+        let light = self.0.interpolate(&next.0, &percent);
+        let dark = Color::new(
+            lerp(self.1.r, next.1.r, percent(4)),
+            lerp(self.1.g, next.1.g, percent(5)),
+            lerp(self.1.b, next.1.b, percent(6)),
+            self.1.a,
+        );
+        (light, dark)
+    }
+
+    fn channel(&self, channel: usize) -> f32 {
+        match channel {
+            0..=3 => self.0.channel(channel),
+            _ => self.1.channel(channel - 4),
+        }
     }
 }
@@ -1,44 +1,116 @@
-use super::CurveTimelines;
+use super::curve::keys_before;
+use super::{Cursor, CurveEvaluation, CurveTimelines};
 use json;
+use skeleton::color::Color;
 use skeleton::error::SkeletonError;
+use skeleton::lint::Lint;
+use skeleton::reduce::Tolerance;
 
 pub struct SlotTimeline {
     attachment: Vec<json::SlotAttachmentTimeline>,
-    color: CurveTimelines<[u8; 4]>,
+    color: CurveTimelines<Color>,
+    two_color: CurveTimelines<(Color, Color)>,
 }
 
 impl SlotTimeline {
-    pub fn from_json(json: json::SlotTimeline) -> Result<SlotTimeline, SkeletonError> {
-        let color = CurveTimelines::from_json_vec(json.color)?;
+    pub fn from_json(
+        json: json::SlotTimeline,
+        curves: CurveEvaluation,
+    ) -> Result<SlotTimeline, SkeletonError> {
+        let color = CurveTimelines::from_json_vec(json.color, curves).map_err(|e| e.at("color"))?;
+        let two_color =
+            CurveTimelines::from_json_vec(json.two_color, curves).map_err(|e| e.at("twoColor"))?;
         Ok(SlotTimeline {
-            attachment: json.attachment.unwrap_or(Vec::new()),
+            attachment: json.attachment.unwrap_or_default(),
             color,
+            two_color,
         })
     }
 
-    pub fn interpolate_color(&self, elapsed: f32) -> [u8; 4] {
-        self.color
+    /// interpolated color, `None` if the color is not animated
+    pub fn interpolate_color(&self, elapsed: f32) -> Option<Color> {
+        self.two_color
             .interpolate(elapsed)
-            .unwrap_or([255, 255, 255, 255])
+            .map(|(light, _)| light)
+            .or_else(|| self.color.interpolate(elapsed))
     }
 
+    /// interpolated dark color, `None` if there is no two color timeline
+    pub fn interpolate_dark_color(&self, elapsed: f32) -> Option<Color> {
+        self.two_color.interpolate(elapsed).map(|(_, dark)| dark)
+    }
+
+    /// attachment name at elapsed, `None` before the first key
     pub fn interpolate_attachment(&self, elapsed: f32) -> Option<Option<&str>> {
-        if self.attachment.is_empty() || elapsed < self.attachment[0].time {
-            None
-        } else {
-            let pos = self
-                .attachment
-                .iter()
-                .position(|a| elapsed < a.time)
-                .unwrap_or(self.attachment.len());
-            Some(self.attachment[pos - 1].name.as_ref().map(|n| &**n))
+        self.attachment_key(keys_before(&self.attachment, |a| a.time, elapsed))
+    }
+
+    /// attachment name at elapsed, starting the search of the key from `cursor`
+    pub fn interpolate_attachment_with_cursor(
+        &self,
+        elapsed: f32,
+        cursor: &mut Cursor,
+    ) -> Option<Option<&str>> {
+        self.attachment_key(cursor.seek(&self.attachment, |a| a.time, elapsed))
+    }
+
+    /// attachment name of the last of `count` keys
+    fn attachment_key(&self, count: usize) -> Option<Option<&str>> {
+        match count {
+            0 => None,
+            count => Some(self.attachment[count - 1].name.as_deref()),
+        }
+    }
+
+    /// names of the non empty timelines, as in the json document
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            ("attachment", self.attachment.is_empty()),
+            ("color", self.color.timelines.is_empty()),
+            ("twoColor", self.two_color.timelines.is_empty()),
+        ]
+        .iter()
+        .filter(|&&(_, empty)| !empty)
+        .map(|&(name, _)| name)
+        .collect()
+    }
+
+    /// removes the color keys recovered within `tolerance` by interpolating the kept ones
+    /// (see `CurveTimelines::simplify`), attachment keys are kept
+    ///
+    /// Returns the number of removed keys
+    pub fn simplify(&mut self, tolerance: &Tolerance, fit: Option<CurveEvaluation>) -> usize {
+        self.color.simplify(tolerance.color, fit) + self.two_color.simplify(tolerance.color, fit)
+    }
+
+    /// reports invalid keys of all timelines, `path` being the path of the slot timelines
+    pub(crate) fn lint(&self, path: &str, lints: &mut Vec<Lint>) {
+        for (i, w) in self.attachment.windows(2).enumerate() {
+            if w[1].time < w[0].time {
+                lints.push(Lint::KeysOutOfOrder(format!(
+                    "{}.attachment[{}]",
+                    path,
+                    i + 1
+                )));
+            }
         }
+        self.color.lint(&format!("{}.color", path), lints);
+        self.two_color.lint(&format!("{}.twoColor", path), lints);
+    }
+
+    /// time of the last key of all timelines
+    pub fn duration(&self) -> f32 {
+        self.attachment
+            .last()
+            .map_or(0f32, |a| a.time)
+            .max(self.color.duration())
+            .max(self.two_color.duration())
     }
 
     pub fn get_attachment_names(&self) -> Vec<&str> {
         self.attachment
             .iter()
-            .filter_map(|t| t.name.as_ref().map(|n| &**n))
+            .filter_map(|t| t.name.as_deref())
             .collect()
     }
 }
@@ -1,17 +1,30 @@
-use super::Bone;
 use super::SkeletonError;
-use super::Slot;
+use std::collections::HashMap;
 
-pub fn bone_index(name: &str, bones: &[Bone]) -> Result<usize, SkeletonError> {
+/// name to index map
+pub type Names = HashMap<String, usize>;
+
+/// adds a name to the map, the first occurrence of a name wins
+///
+/// Returns `false` if the name was already in the map
+pub fn insert_name(names: &mut Names, name: &str, index: usize) -> bool {
+    if names.contains_key(name) {
+        return false;
+    }
+    names.insert(name.to_owned(), index);
+    true
+}
+
+pub fn bone_index(name: &str, bones: &Names) -> Result<usize, SkeletonError> {
     bones
-        .iter()
-        .position(|b| b.name == *name)
+        .get(name)
+        .cloned()
         .ok_or_else(|| SkeletonError::BoneNotFound(name.to_owned()))
 }
 
-pub fn slot_index(name: &str, slots: &[Slot]) -> Result<usize, SkeletonError> {
+pub fn slot_index(name: &str, slots: &Names) -> Result<usize, SkeletonError> {
     slots
-        .iter()
-        .position(|b| b.name == *name)
+        .get(name)
+        .cloned()
         .ok_or_else(|| SkeletonError::SlotNotFound(name.to_owned()))
 }
@@ -1,5 +1,10 @@
+// baseline tests index their first names with `get(0)`
+#![allow(clippy::get_first)]
+
+extern crate serde_json;
 extern crate spine;
 
+use spine::skeleton::loader::SkeletonLoader;
 use std::io::BufReader;
 
 #[test]
@@ -63,3 +68,1785 @@ fn attachement_names() {
             ]
     );
 }
+
+#[test]
+fn scaled_skeleton() {
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let scaled = SkeletonLoader::new()
+        .scale(2.0)
+        .load(BufReader::new(src))
+        .unwrap();
+
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+    let scaled_anim = scaled.get_animated_skin("default", Some("walk")).unwrap();
+
+    let sprites = anim.interpolate(0.3).unwrap();
+    let scaled_sprites = scaled_anim.interpolate(0.3).unwrap();
+    for (s, scaled) in sprites.zip(scaled_sprites) {
+        assert_eq!(s.attachment, scaled.attachment);
+        for i in 0..2 {
+            assert!((s.srt.position[i] * 2.0 - scaled.srt.position[i]).abs() < 1e-3);
+            assert!((s.slot_srt.position[i] * 2.0 - scaled.slot_srt.position[i]).abs() < 1e-3);
+            assert!((s.slot_srt.scale[i] * 2.0 - scaled.slot_srt.scale[i]).abs() < 1e-3);
+        }
+    }
+}
+
+#[test]
+fn loader_options() {
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = SkeletonLoader::new()
+        .skip_animations(true)
+        .load(BufReader::new(src))
+        .unwrap();
+    assert!(doc.get_animations_names().is_empty());
+    assert!(doc.get_skin("default").is_ok());
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "clip", "bone": "root" }],
+        "skins": { "default": { "clip": { "clip": { "type": "clipping" } } } }
+    }"#;
+    assert!(SkeletonLoader::new().load(src.as_bytes()).is_err());
+    let doc = SkeletonLoader::new()
+        .strict(false)
+        .load(src.as_bytes())
+        .unwrap();
+    assert!(doc.get_attachments_names().is_empty());
+}
+
+#[test]
+fn atlas_attachment_loader() {
+    use spine::atlas::Atlas;
+    use spine::skeleton::attachment::Attachment;
+    use spine::skeleton::loader::AtlasAttachmentLoader;
+
+    let atlas = "
+example.png
+size: 256,128
+format: RGBA8888
+filter: Linear,Linear
+repeat: none
+head
+  rotate: false
+  xy: 0, 0
+  size: 64, 64
+  orig: 64, 64
+  offset: 0, 0
+  index: -1
+eyes
+  rotate: true
+  xy: 64, 0
+  size: 32, 16
+  orig: 32, 16
+  offset: 0, 0
+  index: -1
+";
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [
+            { "name": "head", "bone": "root", "attachment": "head" },
+            { "name": "eyes", "bone": "root", "attachment": "eyes" }
+        ],
+        "skins": { "default": {
+            "head": { "head": { "width": 64, "height": 64 } },
+            "eyes": { "eyes": {
+                "type": "mesh",
+                "uvs": [0, 0, 1, 0, 1, 1],
+                "triangles": [0, 1, 2],
+                "vertices": [0, 0, 10, 0, 10, 10]
+            } }
+        } }
+    }"#;
+
+    let atlas = Atlas::from_reader(atlas.as_bytes()).unwrap();
+    assert_eq!(atlas.size, (256, 128));
+    let doc = SkeletonLoader::new()
+        .attachment_loader(AtlasAttachmentLoader::new(atlas).unwrap())
+        .load(src.as_bytes())
+        .unwrap();
+    let skin = doc.get_skin("default").unwrap();
+
+    match skin.find(0, "head") {
+        Some(Attachment::Region(region)) => {
+            assert!(region.texture.is_some());
+            assert_eq!(region.uvs, [0.0, 0.5, 0.0, 0.0, 0.25, 0.0, 0.25, 0.5]);
+        }
+        _ => panic!("head should be a region attachment"),
+    }
+    match skin.find(1, "eyes") {
+        Some(Attachment::Mesh(mesh)) => {
+            assert_eq!(mesh.uvs, [0.25, 0.25, 0.25, 0.0, 0.3125, 0.0]);
+        }
+        _ => panic!("eyes should be a mesh attachment"),
+    }
+
+    // textures of other pages use their page size
+    let pages = "
+example.png
+size: 256,128
+format: RGBA8888
+filter: Linear,Linear
+repeat: none
+head
+  rotate: false
+  xy: 0, 0
+  size: 64, 64
+  orig: 64, 64
+  offset: 0, 0
+  index: -1
+
+example2.png
+size: 64,32
+format: RGBA8888
+filter: Linear,Linear
+repeat: none
+eyes
+  rotate: false
+  xy: 0, 0
+  size: 32, 16
+  orig: 32, 16
+  offset: 0, 0
+  index: -1
+";
+    let mut atlas = Atlas::from_reader(pages.as_bytes()).unwrap();
+    let textures = atlas.by_ref().map(|t| t.unwrap()).collect::<Vec<_>>();
+    assert_eq!(textures.iter().map(|t| t.page).collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(atlas.pages()[1].file, "example2.png");
+    let atlas = Atlas::from_reader(pages.as_bytes()).unwrap();
+    let doc = SkeletonLoader::new()
+        .attachment_loader(AtlasAttachmentLoader::new(atlas).unwrap())
+        .load(src.as_bytes())
+        .unwrap();
+    match doc.get_skin("default").unwrap().find(1, "eyes") {
+        Some(Attachment::Mesh(mesh)) => {
+            assert_eq!(mesh.texture.as_ref().unwrap().page, 1);
+            assert_eq!(mesh.uvs, [0.0, 0.0, 0.5, 0.0, 0.5, 0.5]);
+        }
+        _ => panic!("eyes should be a mesh attachment"),
+    }
+
+    // missing texture regions are reported
+    let atlas = Atlas::from_reader(
+        "\nempty.png\nsize: 8,8\nformat: RGBA8888\nfilter: Linear,Linear\nrepeat: none\n"
+            .as_bytes(),
+    )
+    .unwrap();
+    assert!(SkeletonLoader::new()
+        .attachment_loader(AtlasAttachmentLoader::new(atlas).unwrap())
+        .load(src.as_bytes())
+        .is_err());
+
+    // closures can be used as loaders
+    let doc = SkeletonLoader::new()
+        .attachment_loader(|_: &str, _: &str, attachment: &mut Attachment| {
+            if let Attachment::Mesh(ref mut mesh) = *attachment {
+                mesh.triangles.clear();
+            }
+            Ok(())
+        })
+        .load(src.as_bytes())
+        .unwrap();
+    match doc.get_skin("default").unwrap().find(1, "eyes") {
+        Some(Attachment::Mesh(mesh)) => assert!(mesh.triangles.is_empty()),
+        _ => panic!("eyes should be a mesh attachment"),
+    }
+}
+
+#[test]
+fn spine_versions() {
+    let v4 = r#"{
+        "skeleton": { "hash": "abc", "spine": "4.1.17", "width": 100, "height": 200, "fps": 24 },
+        "bones": [{ "name": "root" }, { "name": "arm", "parent": "root" }],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm", "color": "ffffff80" }],
+        "skins": [{ "name": "default", "attachments": { "arm": { "arm": { "width": 10, "height": 10 } } } }],
+        "animations": { "move": {
+            "bones": { "arm": { "rotate": [
+                { "value": 0, "curve": [0.25, 0, 0.75, 90] },
+                { "time": 1, "value": 90 }
+            ] } },
+            "slots": { "arm": { "rgb": [{ "color": "ff0000" }, { "time": 1, "color": "00ff00" }] } }
+        } }
+    }"#;
+    let v38 = r#"{
+        "skeleton": { "spine": "3.8.99" },
+        "bones": [{ "name": "root" }, { "name": "arm", "parent": "root" }],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm", "color": "ffffff80" }],
+        "skins": [{ "name": "default", "attachments": { "arm": { "arm": { "width": 10, "height": 10 } } } }],
+        "animations": { "move": {
+            "bones": { "arm": { "rotate": [
+                { "angle": 0, "curve": 0.25, "c3": 0.75 },
+                { "time": 1, "angle": 90 }
+            ] } },
+            "slots": { "arm": { "color": [{ "color": "ff000080" }, { "time": 1, "color": "00ff0080" }] } }
+        } }
+    }"#;
+    let v36 = r#"{
+        "skeleton": { "spine": "3.6.53" },
+        "bones": [{ "name": "root" }, { "name": "arm", "parent": "root" }],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm", "color": "ffffff80" }],
+        "skins": { "default": { "arm": { "arm": { "width": 10, "height": 10 } } } },
+        "animations": { "move": {
+            "bones": { "arm": { "rotate": [
+                { "time": 0, "angle": 0, "curve": [0.25, 0, 0.75, 1] },
+                { "time": 1, "angle": 90 }
+            ] } },
+            "slots": { "arm": { "color": [
+                { "time": 0, "color": "ff000080" },
+                { "time": 1, "color": "00ff0080" }
+            ] } }
+        } }
+    }"#;
+
+    let doc = spine::skeleton::Skeleton::from_reader(v4.as_bytes()).unwrap();
+    let header = doc.header();
+    assert_eq!(header.hash, Some("abc".to_owned()));
+    assert_eq!(header.version.unwrap().to_string(), "4.1.17");
+    assert_eq!(
+        (header.width, header.height, header.fps),
+        (100.0, 200.0, 24.0)
+    );
+
+    let docs = [v4, v36, v38]
+        .iter()
+        .map(|src| spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap())
+        .collect::<Vec<_>>();
+    for &time in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+        let sprites = docs
+            .iter()
+            .map(|doc| {
+                let anim = doc.get_animated_skin("default", Some("move")).unwrap();
+                let sprite = anim.interpolate(time).unwrap().next().unwrap();
+                (sprite.srt.rotation, sprite.color)
+            })
+            .collect::<Vec<_>>();
+        for sprite in &sprites[1..] {
+            assert!((sprite.0 - sprites[0].0).abs() < 1e-5);
+            assert_eq!(sprite.1, sprites[0].1);
+        }
+        assert_eq!(sprites[0].1.to_rgba8()[3], 0x80);
+    }
+
+    let v2 = r#"{ "skeleton": { "spine": "2.1.27" }, "bones": [{ "name": "root" }] }"#;
+    assert!(spine::skeleton::Skeleton::from_reader(v2.as_bytes()).is_err());
+
+    // setup colors completing rgb or alpha timelines are checked before the conversion
+    for color in &["zz", "ffffff"] {
+        let src = v4.replace("ffffff80", color);
+        let error = match spine::skeleton::Skeleton::from_reader(src.as_bytes()) {
+            Err(error) => error,
+            Ok(_) => panic!("color {} should be invalid", color),
+        };
+        assert_eq!(error.path(), Some("slots[0].color"));
+        match *error.kind() {
+            spine::skeleton::error::SkeletonError::InvalidColor(ref c) => assert_eq!(c, color),
+            ref e => panic!("unexpected error {:?}", e),
+        }
+    }
+    // as well as the colors of the keys
+    let src = v4.replace(r#""color": "00ff00""#, r#""color": "zz""#);
+    assert!(spine::skeleton::Skeleton::from_reader(src.as_bytes()).is_err());
+}
+
+#[test]
+fn per_channel_curves() {
+    let src = |curve: &str| {
+        format!(
+            r#"{{
+            "skeleton": {{ "spine": "4.0.64" }},
+            "bones": [{{ "name": "root" }}, {{ "name": "arm", "parent": "root" }}],
+            "slots": [{{ "name": "arm", "bone": "arm", "attachment": "arm" }}],
+            "skins": [{{ "name": "default", "attachments": {{ "arm": {{ "arm": {{ "width": 10, "height": 10 }} }} }} }}],
+            "animations": {{ "move": {{ "bones": {{ "arm": {{ "translate": [
+                {{ "curve": {} }},
+                {{ "time": 1, "x": 100, "y": 100 }}
+            ] }} }} }} }}
+        }}"#,
+            curve
+        )
+    };
+
+    // x eases in late, y is linear
+    let doc = spine::skeleton::Skeleton::from_reader(
+        src("[0.9, 0, 1, 0, 0.333, 33.3, 0.667, 66.7]").as_bytes(),
+    )
+    .unwrap();
+    let anim = doc.get_animated_skin("default", Some("move")).unwrap();
+    let sprite = anim.interpolate(0.5).unwrap().next().unwrap();
+    assert!(sprite.srt.position[0] < 20.0);
+    assert!(sprite.srt.position[1] > 40.0);
+
+    // malformed curves are reported as errors
+    for curve in &["[0.25, 0, 0.75]", "[0.25, 0, 0.75, 1, 0]"] {
+        match spine::skeleton::Skeleton::from_reader(src(curve).as_bytes()) {
+            Err(ref e) => match *e.kind() {
+                spine::skeleton::error::SkeletonError::InvalidCurve(_) => (),
+                _ => panic!("curve {} should be invalid", curve),
+            },
+            _ => panic!("curve {} should be invalid", curve),
+        }
+    }
+}
+
+#[test]
+fn bezier_accuracy() {
+    use spine::skeleton::timeline::{Bezier, CurveEvaluation};
+
+    // high resolution sampling of the curve, in f64
+    fn reference(cx1: f64, cy1: f64, cx2: f64, cy2: f64, x: f64) -> f64 {
+        let point = |t: f64| {
+            let u = 1.0 - t;
+            let b = |c1: f64, c2: f64| 3.0 * u * u * t * c1 + 3.0 * u * t * t * c2 + t * t * t;
+            (b(cx1, cx2), b(cy1, cy2))
+        };
+        let samples = 100_000;
+        let mut previous = point(0.0);
+        for i in 1..=samples {
+            let current = point(i as f64 / samples as f64);
+            if current.0 >= x {
+                let percent = (x - previous.0) / (current.0 - previous.0);
+                return previous.1 + percent * (current.1 - previous.1);
+            }
+            previous = current;
+        }
+        1.0
+    }
+
+    let curves = [
+        (0.25, 0.0, 0.75, 1.0),
+        (0.9, 0.0, 1.0, 0.0),
+        (0.0, 0.8, 0.2, 1.0),
+        (0.42, 0.0, 0.58, 1.0),
+        (0.1, -0.5, 0.9, 1.5),
+    ];
+    for &(cx1, cy1, cx2, cy2) in &curves {
+        let exact = Bezier::new(cx1, cy1, cx2, cy2, CurveEvaluation::Exact);
+        let segments = Bezier::new(cx1, cy1, cx2, cy2, CurveEvaluation::Segments);
+        for i in 0..=100 {
+            let x = i as f32 / 100.0;
+            let expected = reference(cx1 as f64, cy1 as f64, cx2 as f64, cy2 as f64, x as f64);
+            assert!((f64::from(exact.percent(x)) - expected).abs() < 1e-4);
+            assert!((f64::from(segments.percent(x)) - expected).abs() < 0.05);
+        }
+    }
+
+    // selectable on the loader
+    let src = r#"{
+        "bones": [{ "name": "root" }, { "name": "arm", "parent": "root" }],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm" }],
+        "skins": { "default": { "arm": { "arm": { "width": 10, "height": 10 } } } },
+        "animations": { "move": { "bones": { "arm": { "rotate": [
+            { "time": 0, "angle": 0, "curve": [0.25, 0, 0.75, 1] },
+            { "time": 1, "angle": 90 }
+        ] } } } }
+    }"#;
+    let doc = SkeletonLoader::new()
+        .curve_evaluation(CurveEvaluation::Exact)
+        .load(src.as_bytes())
+        .unwrap();
+    let anim = doc.get_animated_skin("default", Some("move")).unwrap();
+    let rotation = |time| {
+        anim.interpolate(time)
+            .unwrap()
+            .next()
+            .unwrap()
+            .srt
+            .rotation
+            .to_degrees()
+    };
+    assert!((rotation(0.5) - 45.0).abs() < 1e-3);
+    assert!(rotation(0.25) < 22.5);
+}
+
+#[test]
+fn single_axis_timelines() {
+    let src = r#"{
+        "skeleton": { "spine": "4.1.24" },
+        "bones": [{ "name": "root" }, { "name": "arm", "parent": "root" }],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm" }],
+        "skins": [{ "name": "default", "attachments": { "arm": { "arm": { "width": 10, "height": 10 } } } }],
+        "animations": { "move": { "bones": { "arm": {
+            "translate": [{ "x": 10, "y": 10 }],
+            "translatex": [{ "value": 0 }, { "time": 2, "value": 100 }],
+            "scaley": [{}, { "time": 1, "value": 3 }],
+            "shearx": [{ "value": 10 }]
+        } } } }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", Some("move")).unwrap();
+    assert_eq!(anim.get_duration(), 2.0);
+
+    // translatex overrides x of translate, y is kept
+    let sprite = anim.interpolate(0.5).unwrap().next().unwrap();
+    assert!((sprite.srt.position[0] - 25.0).abs() < 1e-3);
+    assert!((sprite.srt.position[1] - 10.0).abs() < 1e-3);
+    // scaley goes from its default (1) to 3
+    assert!((sprite.srt.scale[1] - 2.0).abs() < 1e-3);
+    assert!((sprite.srt.scale[0] - 1.0).abs() < 1e-3);
+
+    // shears are not applied, and reported as such
+    assert_eq!(
+        doc.validate().lints,
+        vec![spine::skeleton::lint::Lint::IgnoredTimeline(
+            "animations.move.bones.arm.shearx".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn two_color_tinting() {
+    let src = |version: &str, timeline: &str| {
+        format!(
+            r#"{{
+            "skeleton": {{ "spine": "{}" }},
+            "bones": [{{ "name": "root" }}],
+            "slots": [
+                {{ "name": "body", "bone": "root", "attachment": "body", "color": "ffffff80", "dark": "102030" }},
+                {{ "name": "head", "bone": "root", "attachment": "head" }}
+            ],
+            "skins": {{ "default": {{
+                "body": {{ "body": {{ "width": 10, "height": 10 }} }},
+                "head": {{ "head": {{ "width": 10, "height": 10 }} }}
+            }} }},
+            "animations": {{ "tint": {{ "slots": {{ "head": {{ {} }} }} }} }}
+        }}"#,
+            version, timeline
+        )
+    };
+
+    let check = |doc: &str| {
+        let doc = spine::skeleton::Skeleton::from_reader(doc.as_bytes()).unwrap();
+        let anim = doc.get_animated_skin("default", Some("tint")).unwrap();
+        let sprites = anim.interpolate(0.5).unwrap().collect::<Vec<_>>();
+        assert_eq!(sprites[0].color.to_rgba8(), [255, 255, 255, 128]);
+        let dark = sprites[0].dark_color.unwrap();
+        assert_eq!(dark.to_rgba8(), [0x10, 0x20, 0x30, 255]);
+        assert_eq!(sprites[1].color.a, 1.0);
+        let dark = sprites[1].dark_color.unwrap();
+        assert_eq!(dark.to_rgba8(), [50, 0, 100, 255]);
+
+        let baked = anim.bake(30.0, Some(Default::default()));
+        let frame = baked.frame(0.5);
+        let dark = baked.slots[0].dark_color(frame).unwrap();
+        assert_eq!(dark.to_rgba8(), [0x10, 0x20, 0x30, 255]);
+        let dark = baked.slots[1].dark_color(frame).unwrap();
+        assert_eq!(dark.to_rgba8(), [50, 0, 100, 255]);
+        assert!(baked.slots[1].colors.len() < 31);
+    };
+
+    check(&src(
+        "3.7.94",
+        r#""twoColor": [
+            { "time": 0, "light": "ffffffff", "dark": "000000" },
+            { "time": 1, "light": "ffffffff", "dark": "6400c8" }
+        ]"#,
+    ));
+    check(&src(
+        "4.1.24",
+        r#""rgb2": [
+            { "light": "ffffff", "dark": "000000" },
+            { "time": 1, "light": "ffffff", "dark": "6400c8" }
+        ]"#,
+    ));
+}
+
+#[test]
+fn blend_modes() {
+    use spine::skeleton::slot::BlendMode;
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body" },
+            { "name": "glow", "bone": "root", "attachment": "glow", "blend": "additive" },
+            { "name": "shadow", "bone": "root", "attachment": "shadow", "blend": "multiply" }
+        ],
+        "skins": { "default": {
+            "body": { "body": { "width": 10, "height": 10 } },
+            "glow": { "glow": { "width": 10, "height": 10 } },
+            "shadow": { "shadow": { "width": 10, "height": 10 } }
+        } },
+        "animations": {}
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", None).unwrap();
+    let modes = anim
+        .interpolate(0.0)
+        .unwrap()
+        .map(|s| s.blend_mode)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        modes,
+        vec![BlendMode::Normal, BlendMode::Additive, BlendMode::Multiply]
+    );
+}
+
+#[test]
+fn rgb_and_alpha_timelines() {
+    use spine::skeleton::loader::SkeletonLoader;
+    use spine::skeleton::timeline::CurveEvaluation;
+
+    let load = |slot: &str| {
+        let src = format!(
+            r#"{{
+            "skeleton": {{ "spine": "4.1.24" }},
+            "bones": [{{ "name": "root" }}],
+            "slots": [{{ "name": "body", "bone": "root", "attachment": "body", "color": "ffffff80" }}],
+            "skins": [{{ "name": "default", "attachments": {{ "body": {{ "body": {{ "width": 10, "height": 10 }} }} }} }}],
+            "animations": {{ "fade": {{ "slots": {{ "body": {} }} }} }}
+        }}"#,
+            slot
+        );
+        SkeletonLoader::new()
+            .curve_evaluation(CurveEvaluation::Exact)
+            .load(src.as_bytes())
+            .unwrap()
+    };
+    let color = |doc: &spine::skeleton::Skeleton, time: f32| {
+        let anim = doc.get_animated_skin("default", Some("fade")).unwrap();
+        let sprite = anim.interpolate(time).unwrap().next().unwrap();
+        sprite.color
+    };
+
+    // neither timeline is ignored
+    let doc = load(r#"{ "rgb": [{ "color": "ff0000" }], "alpha": [{ "value": 0.25 }] }"#);
+    assert_eq!(color(&doc, 0.0).to_rgba8(), [255, 0, 0, 64]);
+
+    // keys at different times, with a stepped alpha
+    let doc = load(
+        r#"{
+        "rgb": [{ "color": "ff0000" }, { "time": 1, "color": "00ff00" }],
+        "alpha": [{ "value": 0.25, "curve": "stepped" }, { "time": 0.5, "value": 1 }]
+    }"#,
+    );
+    assert_eq!(color(&doc, 0.25).to_rgba8(), [192, 64, 0, 64]);
+    assert_eq!(color(&doc, 0.49).to_rgba8()[3], 64);
+    assert_eq!(color(&doc, 0.5).to_rgba8(), [128, 128, 0, 255]);
+    assert_eq!(color(&doc, 0.75).to_rgba8(), [64, 192, 0, 255]);
+
+    // bezier curves split at the keys of the other timeline keep their shape
+    let rgb = r#""rgb": [
+        { "color": "ff0000", "curve": [0.5, 1, 0.75, 0, 0.2, 0, 0.3, 1, 0.25, 0, 0.75, 0] },
+        { "time": 1, "color": "00ff00" }
+    ]"#;
+    let alpha = r#""alpha": [
+        { "value": 0.1, "curve": [0.1, 0.1, 0.2, 0.5] },
+        { "time": 0.3, "value": 0.5 },
+        { "time": 0.6, "value": 1 },
+        { "time": 1, "value": 0.2 }
+    ]"#;
+    let (merged, rgb_only, alpha_only) = (
+        load(&format!("{{ {}, {} }}", rgb, alpha)),
+        load(&format!("{{ {} }}", rgb)),
+        load(&format!("{{ {} }}", alpha)),
+    );
+    for i in 0..=20 {
+        let time = i as f32 / 20.0;
+        let (c, rgb, alpha) = (
+            color(&merged, time),
+            color(&rgb_only, time),
+            color(&alpha_only, time),
+        );
+        for (merged, expected) in [(c.r, rgb.r), (c.g, rgb.g), (c.b, rgb.b), (c.a, alpha.a)] {
+            assert!((merged - expected).abs() < 0.01, "{} at {}", merged, time);
+        }
+    }
+}
+
+#[test]
+fn color_composition() {
+    use spine::skeleton::color::Color;
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root", "attachment": "body", "color": "ff000080" }],
+        "skins": { "default": {
+            "body": { "body": { "width": 10, "height": 10, "color": "8080ffff" } }
+        } },
+        "animations": { "fade": { "slots": { "body": { "color": [
+            { "time": 0, "color": "ffffff00" },
+            { "time": 1, "color": "ffffffff" }
+        ] } } } }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+
+    // slot color is multiplied by the attachment color
+    let setup = doc.get_animated_skin("default", None).unwrap();
+    let sprite = setup.interpolate(0.0).unwrap().next().unwrap();
+    assert_eq!(sprite.color.to_rgba8(), [128, 0, 0, 128]);
+    assert_eq!(sprite.premultiplied_color().to_rgba8(), [64, 0, 0, 128]);
+
+    // fades reach the end value exactly and are not truncated
+    let fade = doc.get_animated_skin("default", Some("fade")).unwrap();
+    let alpha = |time: f32| {
+        let sprite = fade.interpolate(time).unwrap().next().unwrap();
+        sprite.color.a
+    };
+    assert_eq!(alpha(1.0), 1.0);
+    assert!((alpha(0.999) - 0.999).abs() < 1e-5);
+    assert_eq!(
+        Color::new(1.0, 1.0, 1.0, 0.999).to_rgba8(),
+        [255, 255, 255, 255]
+    );
+}
+
+#[test]
+fn srt_matrices() {
+    use spine::skeleton::srt::SRT;
+
+    // the matrices transform points as `SRT::transform`, with a non uniform scale
+    let srt = SRT::new(2.0, -3.0, 30.0, 5.0, 7.0);
+    let (m3, m4) = (srt.to_matrix3(), srt.to_matrix4());
+    for &p in &[[1.0, 0.0], [0.0, 1.0], [1.5, -2.0]] {
+        let expected = srt.transform(p);
+        let from3 = [
+            m3[0][0] * p[0] + m3[1][0] * p[1] + m3[2][0],
+            m3[0][1] * p[0] + m3[1][1] * p[1] + m3[2][1],
+        ];
+        let from4 = [
+            m4[0][0] * p[0] + m4[1][0] * p[1] + m4[3][0],
+            m4[0][1] * p[0] + m4[1][1] * p[1] + m4[3][1],
+        ];
+        for v in [from3, from4] {
+            assert!((v[0] - expected[0]).abs() < 1e-5 && (v[1] - expected[1]).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn skeleton_root_transform() {
+    use spine::skeleton::color::Color;
+
+    let src = r#"{
+        "bones": [
+            { "name": "root", "x": 10, "rotation": 20 },
+            { "name": "arm", "parent": "root", "x": 5, "y": 3, "rotation": 30, "scaleX": 2 }
+        ],
+        "slots": [{ "name": "arm", "bone": "arm", "attachment": "arm" }],
+        "skins": { "default": { "arm": { "arm": { "x": 1, "width": 10, "height": 4, "rotation": 10 } } } },
+        "animations": {}
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+
+    // world position of a point of the sprite quad
+    let corner = |anim: &spine::skeleton::animation::skin::SkinAnimation| {
+        let sprite = anim.interpolate(0.0).unwrap().next().unwrap();
+        let m = sprite.to_matrix3();
+        let (x, y) = (1.0, 0.5);
+        (
+            m[0][0] * x + m[1][0] * y + m[2][0],
+            m[0][1] * x + m[1][1] * y + m[2][1],
+        )
+    };
+
+    let anim = doc.get_animated_skin("default", None).unwrap();
+    let (x, y) = corner(&anim);
+
+    // flipped skeleton mirrors the pose around the root position
+    let mut flipped = doc.get_animated_skin("default", None).unwrap();
+    flipped.set_position(100.0, 50.0);
+    flipped.set_scale(-1.0, 1.0);
+    flipped.set_color(Color::new(1.0, 1.0, 1.0, 0.5));
+    let (fx, fy) = corner(&flipped);
+    assert!((fx - (100.0 - x)).abs() < 1e-3, "{} {}", fx, x);
+    assert!((fy - (50.0 + y)).abs() < 1e-3, "{} {}", fy, y);
+
+    let sprite = flipped.interpolate(0.0).unwrap().next().unwrap();
+    assert_eq!(sprite.color.a, 0.5);
+}
+
+#[test]
+fn combined_skins() {
+    use spine::skeleton::skin::Skin;
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body" },
+            { "name": "hat", "bone": "root", "attachment": "hat" },
+            { "name": "weapon", "bone": "root", "attachment": "weapon" }
+        ],
+        "skins": {
+            "default": { "body": { "body": { "width": 10, "height": 10 } } },
+            "wizard": { "hat": { "hat": { "name": "wizard_hat", "width": 10, "height": 10 } } },
+            "knight": {
+                "hat": { "hat": { "name": "helmet", "width": 10, "height": 10 } },
+                "weapon": { "weapon": { "name": "sword", "width": 10, "height": 10 } }
+            }
+        },
+        "animations": {}
+    }"#;
+    let mut doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let attachments = |doc: &spine::skeleton::Skeleton, skin: &str| {
+        let anim = doc.get_animated_skin(skin, None).unwrap();
+        let sprites = anim.interpolate(0.0).unwrap();
+        sprites.map(|s| s.attachment.to_owned()).collect::<Vec<_>>()
+    };
+
+    // knight with a wizard hat
+    let skin = Skin::combine(&[
+        doc.get_skin("knight").unwrap(),
+        doc.get_skin("wizard").unwrap(),
+    ]);
+    doc.add_skin("mixed", skin);
+    assert_eq!(attachments(&doc, "mixed"), ["body", "wizard_hat", "sword"]);
+
+    // unarmed knight
+    let weapon = doc.get_slot_index("weapon").unwrap();
+    assert_eq!(weapon, doc.find_slot("weapon").unwrap().index());
+    let mut skin = doc.get_skin("knight").unwrap().clone();
+    let slots = skin.slot_attachments().map(|(i, _)| i).collect::<Vec<_>>();
+    assert_eq!(slots, [1, weapon]);
+    assert!(skin.remove_attachment(weapon, "weapon").is_some());
+    doc.add_skin("unarmed", skin);
+    assert_eq!(attachments(&doc, "unarmed"), ["body", "helmet"]);
+
+    // wizard with a sword
+    let mut skin = doc.get_skin("wizard").unwrap().clone();
+    assert!(skin.copy_attachment(doc.get_skin("knight").unwrap(), weapon, "weapon"));
+    doc.add_skin("armed", skin);
+    assert_eq!(attachments(&doc, "armed"), ["body", "wizard_hat", "sword"]);
+}
+
+#[test]
+fn skin_required_bones() {
+    let src = r#"{
+        "skeleton": { "spine": "3.8.99" },
+        "bones": [
+            { "name": "root" },
+            { "name": "cape", "parent": "root", "skin": true },
+            { "name": "cape-tip", "parent": "cape", "skin": true }
+        ],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body" },
+            { "name": "cape", "bone": "cape-tip", "attachment": "cape" }
+        ],
+        "skins": [
+            { "name": "default", "attachments": {
+                "body": { "body": { "width": 10, "height": 10 } },
+                "cape": { "cape": { "width": 10, "height": 10 } }
+            } },
+            { "name": "hero", "bones": ["cape-tip"], "ik": ["cape-ik"], "path": ["cape-path"] }
+        ],
+        "ik": [{ "name": "cape-ik", "bones": ["cape"], "target": "cape-tip" }],
+        "path": [{ "name": "cape-path", "bones": ["cape"], "target": "cape" }],
+        "animations": {}
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let hero = doc.get_skin("hero").unwrap();
+    assert_eq!(hero.bones, [2]);
+    assert_eq!(hero.ik_constraints, ["cape-ik"]);
+    assert!(hero.transform_constraints.is_empty());
+    assert_eq!(hero.path_constraints, ["cape-path"]);
+
+    let attachments = |skin: &str| {
+        let anim = doc.get_animated_skin(skin, None).unwrap();
+        let sprites = anim.interpolate(0.0).unwrap();
+        sprites.map(|s| s.attachment.to_owned()).collect::<Vec<_>>()
+    };
+    assert_eq!(attachments("default"), ["body"]);
+    assert_eq!(attachments("hero"), ["body", "cape"]);
+
+    // skin constraints are looked up among the constraints of their kind
+    for &(from, to, path) in &[
+        (
+            r#""ik": ["cape-ik"]"#,
+            r#""ik": ["nope"]"#,
+            "skins.hero.ik[0]",
+        ),
+        (
+            r#""path": ["cape-path"]"#,
+            r#""path": ["cape-ik"]"#,
+            "skins.hero.path[0]",
+        ),
+    ] {
+        let src = src.replace(from, to);
+        match spine::skeleton::Skeleton::from_reader(src.as_bytes()) {
+            Err(ref e) => {
+                assert_eq!(e.path(), Some(path));
+                match *e.kind() {
+                    spine::skeleton::error::SkeletonError::ConstraintNotFound(_) => (),
+                    ref e => panic!("unexpected error {:?}", e),
+                }
+            }
+            Ok(_) => panic!("{} should not be found", to),
+        }
+    }
+}
+
+#[test]
+fn find_handles() {
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+
+    assert_eq!(doc.find_bone("root").unwrap().index(), 0);
+    assert!(doc.find_bone("crawl").is_none());
+    assert!(doc.find_slot("crawl").is_none());
+
+    let walk = doc.find_animation("walk").unwrap();
+    let jump = doc.find_animation("jump").unwrap();
+    assert!(walk != jump);
+    assert_eq!(doc.find_animation("walk"), Some(walk));
+    assert!(doc.find_animation("crawl").is_none());
+}
+
+#[test]
+fn read_accessors() {
+    let src = r#"{
+        "bones": [
+            { "name": "root" },
+            { "name": "hip", "parent": "root" },
+            { "name": "leg", "parent": "hip" },
+            { "name": "torso", "parent": "hip" }
+        ],
+        "slots": [{ "name": "leg", "bone": "leg", "color": "ff000080" }],
+        "animations": { "walk": {
+            "bones": { "leg": {
+                "rotate": [{ "time": 0, "angle": 0 }, { "time": 2, "angle": 30 }],
+                "translate": [{ "time": 0, "x": 0 }]
+            }, "torso": { "rotate": [{ "time": 1, "angle": 5 }] }, "hip": { "scale": [{}] } },
+            "slots": { "leg": { "attachment": [{ "time": 1.5, "name": null }] } }
+        } }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+
+    let hip = doc.find_bone("hip").unwrap();
+    assert_eq!(doc.bone(hip).unwrap().name, "hip");
+    assert_eq!(doc.parent(hip), doc.find_bone("root"));
+    let children = doc
+        .children(hip)
+        .map(|b| &*doc.bone(b).unwrap().name)
+        .collect::<Vec<_>>();
+    assert_eq!(children, ["leg", "torso"]);
+    assert_eq!(doc.bones().len(), 4);
+
+    let leg = doc.find_slot("leg").unwrap();
+    assert_eq!(doc.slot(leg).unwrap().color.to_rgba8(), [255, 0, 0, 128]);
+    assert_eq!(doc.slots().len(), 1);
+
+    let walk = doc.animation(doc.find_animation("walk").unwrap()).unwrap();
+    assert_eq!(walk.name(), "walk");
+    assert_eq!(walk.duration(), 2.0);
+    let bones = walk
+        .bone_timelines()
+        .map(|(b, t)| (&*doc.bone(b).unwrap().name, t.names()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        bones,
+        [
+            ("hip", vec!["scale"]),
+            ("leg", vec!["translate", "rotate"]),
+            ("torso", vec!["rotate"])
+        ]
+    );
+    let slots = walk
+        .slot_timelines()
+        .map(|(s, t)| (s, t.names()))
+        .collect::<Vec<_>>();
+    assert_eq!(slots, [(leg, vec!["attachment"])]);
+
+    // handles of a bigger skeleton are not valid here
+    let src: &[u8] = include_bytes!("example.json");
+    let other = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let last = other
+        .find_bone(&other.bones().last().unwrap().name)
+        .unwrap();
+    assert!(last.index() >= doc.bones().len());
+    assert!(doc.bone(last).is_none());
+    assert_eq!(doc.parent(last), None);
+}
+
+#[test]
+fn debug_geometry() {
+    let src = r#"{
+        "bones": [
+            { "name": "root", "length": 10 },
+            { "name": "arm", "parent": "root", "x": 10, "rotation": 90, "length": 5 }
+        ],
+        "slots": [
+            { "name": "hand", "bone": "arm", "attachment": "hand" },
+            { "name": "skin", "bone": "arm", "attachment": "skin" },
+            { "name": "hit", "bone": "root", "attachment": "hit" }
+        ],
+        "skins": { "default": {
+            "hand": { "hand": { "x": 2, "width": 4, "height": 4 } },
+            "skin": { "skin": {
+                "type": "mesh", "hull": 3,
+                "uvs": [0, 0, 1, 0, 1, 1, 0, 1],
+                "vertices": [0, 0, 1, 0, 1, 1, 0, 1],
+                "triangles": [0, 1, 2, 0, 2, 3]
+            } },
+            "hit": { "hit": {
+                "type": "boundingbox", "vertexCount": 2,
+                "vertices": [1, 0, 0, 0, 1, 2, 0, 0, 0, 0.5, 1, 0, 1, 0.5]
+            } }
+        } },
+        "animations": {}
+    }"#;
+    let close = |a: [f32; 2], b: [f32; 2]| (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4;
+
+    let doc = SkeletonLoader::new()
+        .scale(2.0)
+        .load(src.as_bytes())
+        .unwrap();
+    assert_eq!(
+        doc.bone(doc.find_bone("arm").unwrap()).unwrap().length,
+        10.0
+    );
+
+    let anim = doc.get_animated_skin("default", None).unwrap();
+    let debug = anim.debug(0.0);
+
+    assert!(close(debug.bones[0].0, [0.0, 0.0]));
+    assert!(close(debug.bones[0].1, [20.0, 0.0]));
+    assert!(close(debug.bones[1].0, [20.0, 0.0]));
+    assert!(close(debug.bones[1].1, [20.0, 10.0]));
+
+    assert!(close(debug.slots[0], [20.0, 4.0]));
+    assert!(close(debug.slots[1], [20.0, 0.0]));
+
+    let hull = &debug.hulls[0];
+    assert_eq!(hull.len(), 3);
+    assert!(close(hull[1], [20.0, 2.0]));
+    assert!(close(hull[2], [18.0, 2.0]));
+
+    // weighted to root and arm
+    let outline = &debug.bounding_boxes[0];
+    assert_eq!(outline.len(), 2);
+    assert!(close(outline[0], [0.0, 0.0]));
+    assert!(close(outline[1], [9.0, 0.0]));
+
+    // meshes are drawn, bounding boxes are not
+    let sprites = anim.interpolate(0.0).unwrap().count();
+    assert_eq!(sprites, 2);
+}
+
+#[test]
+fn load_errors() {
+    let src = |bones: &str, slots: &str, animations: &str| {
+        format!(
+            r#"{{ "bones": [{}], "slots": [{}], "animations": {{ {} }} }}"#,
+            bones, slots, animations
+        )
+    };
+    let error = |src: String| match spine::skeleton::Skeleton::from_reader(src.as_bytes()) {
+        Err(e) => e.to_string(),
+        Ok(_) => panic!("{} should not load", src),
+    };
+
+    let root = r#"{ "name": "root" }, { "name": "leg", "parent": "root" }"#;
+    assert_eq!(
+        error(src(
+            r#"{ "name": "root" }, { "name": "leg", "parent": "hip" }"#,
+            "",
+            ""
+        )),
+        "bones[1].parent: Cannot find bone 'hip'"
+    );
+    assert_eq!(
+        error(src(
+            root,
+            r#"{ "name": "leg", "bone": "leg", "color": "red" }"#,
+            ""
+        )),
+        "slots[0].color: Invalid color 'red': expecting hexadecimal RRGGBBAA or RRGGBB"
+    );
+    assert_eq!(
+        error(src(
+            root,
+            "",
+            r#""walk": { "bones": { "leg": { "rotate": [
+                { "time": 0, "angle": 0 },
+                { "time": 1, "angle": 10, "curve": [0.5, 0] }
+            ] } } }"#
+        )),
+        "animations.walk.bones.leg.rotate[1]: \
+         Invalid bezier curve: expecting 4 values per channel, found 2"
+    );
+    assert_eq!(
+        error(src(root, "", r#""walk": { "bones": { "arm": {} } }"#)),
+        "animations.walk.bones.arm: Cannot find bone 'arm'"
+    );
+    assert_eq!(
+        error(src(
+            root,
+            r#"{ "name": "leg", "bone": "leg" }, { "name": "leg", "bone": "root" }"#,
+            ""
+        )),
+        "slots[1]: Duplicate name 'leg'"
+    );
+
+    // values of the wrong type
+    let path = |src: String| match spine::skeleton::Skeleton::from_reader(src.as_bytes()) {
+        Err(e) => e.path().map(str::to_owned),
+        Ok(_) => panic!("{} should not load", src),
+    };
+    let bones = r#"{ "name": "root" }, { "name": "leg", "parent": "root", "x": "ten" }"#;
+    assert_eq!(path(src(bones, "", "")), Some("bones[1].x".to_owned()));
+    let walk = r#""walk": { "bones": { "leg": { "rotate": [{ "time": 0 }, { "time": [] }] } } }"#;
+    assert_eq!(
+        path(src(root, "", walk)),
+        Some("animations.walk.bones.leg.rotate[1].time".to_owned())
+    );
+    assert_eq!(path("{ \"bones\": [".to_owned()), Some("bones".to_owned()));
+
+    let doc = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root" }],
+        "skins": { "default": { "body": { "body": { "type": "clipping" } } } }
+    }"#;
+    match spine::skeleton::Skeleton::from_reader(doc.as_bytes()) {
+        Err(e) => {
+            assert_eq!(e.path(), Some("skins.default.body.body"));
+            assert_eq!(
+                e.kind().to_string(),
+                "Unsupported attachment type 'clipping'"
+            );
+        }
+        Ok(_) => panic!("clipping attachments are not supported"),
+    }
+
+    // weighted vertices with more bones than values
+    for attachment in &[
+        r#"{ "type": "mesh", "vertices": [1e9], "triangles": [] }"#,
+        r#"{ "type": "boundingbox", "vertices": [2, 0, 0, 0, 1] }"#,
+    ] {
+        let doc = format!(
+            r#"{{
+            "bones": [{{ "name": "root" }}],
+            "slots": [{{ "name": "body", "bone": "root" }}],
+            "skins": {{ "default": {{ "body": {{ "body": {} }} }} }}
+        }}"#,
+            attachment
+        );
+        match spine::skeleton::Skeleton::from_reader(doc.as_bytes()) {
+            Err(e) => {
+                assert_eq!(e.path(), Some("skins.default.body.body.vertices"));
+                assert_eq!(
+                    e.kind().to_string(),
+                    "Weighted vertex at 0 has too many bones"
+                );
+            }
+            Ok(_) => panic!("{} should not load", attachment),
+        }
+    }
+
+    let doc = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root" }],
+        "skins": { "default": { "body": { "body": { "color": "FFF" } } } }
+    }"#;
+    assert_eq!(
+        error(doc.to_owned()),
+        "skins.default.body.body.color: \
+         Invalid color 'FFF': expecting hexadecimal RRGGBBAA or RRGGBB"
+    );
+}
+
+#[test]
+fn validation() {
+    use spine::skeleton::lint::Lint;
+    use spine::skeleton::loader::SkeletonLoader;
+
+    let doc = r#"{
+        "bones": [
+            { "name": "root" },
+            { "name": "hip", "parent": "root" },
+            { "name": "tail", "parent": "hip" },
+            { "name": "hip", "parent": "root" }
+        ],
+        "slots": [
+            { "name": "body", "bone": "hip", "attachment": "body" },
+            { "name": "shadow", "bone": "root" }
+        ],
+        "skins": { "default": { "body": {
+            "body": {},
+            "hurt": {},
+            "mesh": {
+                "type": "mesh", "uvs": [0, 0, 1, 0, 1, 1],
+                "vertices": [0, 0, 1, 0, 1, 1], "triangles": [0, 1, 3]
+            }
+        } } },
+        "animations": { "walk": {
+            "bones": { "hip": { "rotate": [
+                { "time": 0, "angle": 0, "curve": [0.25, 0, 0.75, 1] },
+                { "time": 0, "angle": 10 },
+                { "time": 1, "angle": 20 },
+                { "time": 0.5, "angle": 0 }
+            ] } },
+            "slots": { "body": { "attachment": [
+                { "time": 0, "name": "mesh" },
+                { "time": 0.5, "name": "broken" }
+            ] } }
+        } }
+    }"#;
+    let skeleton = SkeletonLoader::new()
+        .strict(false)
+        .load(doc.as_bytes())
+        .unwrap();
+    let report = skeleton.validate();
+    let expected = [
+        Lint::DuplicateName("bones[3]".to_owned()),
+        Lint::ZeroLengthCurve("animations.walk.bones.hip.rotate[0]".to_owned()),
+        Lint::KeysOutOfOrder("animations.walk.bones.hip.rotate[3]".to_owned()),
+        Lint::MissingAttachment(
+            "animations.walk.slots.body.attachment".to_owned(),
+            "broken".to_owned(),
+        ),
+        Lint::InvalidTriangle("skins.default.body.mesh.triangles[2]".to_owned(), 3),
+        Lint::UnusedSlot("slots[1]".to_owned()),
+        Lint::UnusedAttachment("skins.default.body.hurt".to_owned()),
+        Lint::UnusedBone("bones[3]".to_owned()),
+        Lint::UnusedBone("bones[2]".to_owned()),
+    ];
+    assert_eq!(report.lints, expected);
+    assert_eq!(
+        report.to_string().lines().next(),
+        Some("bones[3]: name is already used")
+    );
+
+    let src: &[u8] = include_bytes!("example.json");
+    let skeleton = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    assert_eq!(
+        skeleton.validate().lints,
+        [Lint::UnusedAttachment(
+            "skins.default.eyes.eyes-closed".to_owned()
+        )]
+    );
+
+    // bones used by constraints, attachment keys of a given skin
+    let doc = r#"{
+        "bones": [
+            { "name": "root" },
+            { "name": "arm", "parent": "root" },
+            { "name": "hand", "parent": "arm" },
+            { "name": "target", "parent": "root" },
+            { "name": "rail", "parent": "root" },
+            { "name": "lost", "parent": "root" }
+        ],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body" },
+            { "name": "rail", "bone": "rail" }
+        ],
+        "ik": [{ "name": "reach", "bones": ["arm", "hand"], "target": "target" }],
+        "path": [{ "name": "follow", "bones": ["hand"], "target": "rail" }],
+        "skins": {
+            "default": { "body": { "body": {} }, "rail": { "rail": {} } },
+            "hurt": { "body": { "hurt": {} } },
+            "sick": { "body": { "sick": {} } }
+        },
+        "animations": { "pain": { "slots": { "body": { "attachment": [{ "name": "hurt" }] } } } }
+    }"#;
+    let skeleton = spine::skeleton::Skeleton::from_reader(doc.as_bytes()).unwrap();
+    let constraints = skeleton.constraints();
+    assert_eq!(constraints[0].bones, [1, 2]);
+    assert_eq!(constraints[1].target, 1);
+    let lints = skeleton.validate().lints;
+    let unused = lints
+        .iter()
+        .filter(|l| matches!(l, Lint::UnusedBone(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(unused, [&Lint::UnusedBone("bones[5]".to_owned())]);
+    let missing = Lint::MissingAttachment(
+        "animations.pain.slots.body.attachment".to_owned(),
+        "hurt".to_owned(),
+    );
+    assert!(!lints.contains(&missing));
+    assert!(!skeleton
+        .validate_skin("hurt")
+        .unwrap()
+        .lints
+        .contains(&missing));
+    assert!(skeleton
+        .validate_skin("sick")
+        .unwrap()
+        .lints
+        .contains(&missing));
+    assert!(skeleton.validate_skin("healthy").is_err());
+
+    // skins sharing a name, the first one is kept
+    let doc = r#"{
+        "skeleton": { "spine": "3.8.99" },
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root", "attachment": "body" }],
+        "skins": [
+            { "name": "default", "attachments": { "body": { "body": {} } } },
+            { "name": "default" }
+        ]
+    }"#;
+    match spine::skeleton::Skeleton::from_reader(doc.as_bytes()) {
+        Err(ref e) => assert_eq!(e.to_string(), "skins[1]: Duplicate name 'default'"),
+        Ok(_) => panic!("duplicate skins should be rejected"),
+    }
+    let skeleton = SkeletonLoader::new()
+        .strict(false)
+        .load(doc.as_bytes())
+        .unwrap();
+    assert_eq!(
+        skeleton.validate().lints,
+        [Lint::DuplicateName("skins[1]".to_owned())]
+    );
+    let skin = skeleton.get_skin("default").unwrap();
+    assert_eq!(skin.attachments().len(), 1);
+}
+
+#[test]
+fn pose_sampling() {
+    use spine::skeleton::animation::pose::Pose;
+    use spine::skeleton::animation::render::{Framebuffer, Image};
+
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+
+    let mut pose = Pose::new();
+    for &time in &[0.0, 0.3, 0.65, 0.1] {
+        anim.sample(time, &mut pose);
+        assert_eq!(pose.time(), time);
+        assert_eq!(pose.srts().len(), doc.bones().len());
+
+        let sprites = anim.interpolate(time).unwrap().collect::<Vec<_>>();
+        let pose_sprites = anim.sprites(&pose).collect::<Vec<_>>();
+        assert_eq!(sprites.len(), pose_sprites.len());
+        for (s, p) in sprites.iter().zip(pose_sprites.iter()) {
+            assert_eq!(s.attachment, p.attachment);
+            assert_eq!(s.color, p.color);
+            assert_eq!(s.blend_mode, p.blend_mode);
+            assert_eq!(s.to_matrix3(), p.to_matrix3());
+        }
+    }
+
+    // poses not sampled by the animation have no sprite
+    assert_eq!(anim.sprites(&Pose::new()).count(), 0);
+    let small = r#"{ "bones": [{ "name": "root" }], "skins": { "default": {} } }"#;
+    let small = spine::skeleton::Skeleton::from_reader(small.as_bytes()).unwrap();
+    small
+        .get_animated_skin("default", None)
+        .unwrap()
+        .sample(0.0, &mut pose);
+    assert_eq!(anim.sprites(&pose).count(), 0);
+    let mut target = Framebuffer::new(4, 4);
+    target.draw(&anim, &pose, &[Image::new(1, 1, &[255; 4])]);
+    assert!(target.to_rgba8().iter().all(|&c| c == 0));
+}
+
+#[test]
+fn batch_sampling() {
+    use spine::skeleton::animation::batch::BatchPose;
+    use spine::skeleton::animation::pose::Pose;
+
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let mut anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+    anim.set_position(10.0, -5.0);
+    anim.set_scale(-2.0, 2.0);
+
+    // more instances than SIMD lanes, with a remainder, times going back once
+    let mut times = (0..11).map(|i| i as f32 * 0.09).collect::<Vec<_>>();
+    times.swap(3, 8);
+    let (mut batch, mut pose) = (BatchPose::new(), Pose::new());
+    anim.sample_batch(&times, &mut batch);
+    assert_eq!(batch.instances(), times.len());
+    assert_eq!(batch.bones(), doc.bones().len());
+
+    for (instance, &time) in times.iter().enumerate() {
+        anim.sample(time, &mut pose);
+        for (bone, srt) in pose.srts().iter().enumerate() {
+            let batched = batch.srt(instance, bone);
+            for i in 0..2 {
+                assert!((batched.position[i] - srt.position[i]).abs() < 1e-4);
+                assert!((batched.scale[i] - srt.scale[i]).abs() < 1e-6);
+            }
+            assert!((batched.cos - srt.cos).abs() < 1e-6);
+            assert!((batched.sin - srt.sin).abs() < 1e-6);
+            assert_eq!(batch.positions(bone).0[instance], batched.position[0]);
+        }
+    }
+}
+
+#[test]
+fn keyframe_search() {
+    use spine::skeleton::timeline::Cursor;
+
+    let doc = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [{ "name": "body", "bone": "root", "attachment": "a" }],
+        "animations": { "blink": {
+            "bones": { "root": { "rotate": [
+                { "time": 0.5, "angle": 0 },
+                { "time": 1, "angle": 90 },
+                { "time": 1, "angle": 10 },
+                { "time": 2, "angle": 30 },
+                { "time": 3, "angle": 40 }
+            ] } },
+            "slots": { "body": { "attachment": [
+                { "time": 0.5, "name": "b" },
+                { "time": 1, "name": "c" },
+                { "time": 2, "name": null },
+                { "time": 4, "name": "a" }
+            ] } }
+        } }
+    }"#;
+    let skeleton = spine::skeleton::Skeleton::from_reader(doc.as_bytes()).unwrap();
+    let animation = skeleton
+        .animation(skeleton.find_animation("blink").unwrap())
+        .unwrap();
+    let (_, bone) = animation.bone_timelines().next().unwrap();
+    let (_, slot) = animation.slot_timelines().next().unwrap();
+
+    let expected = |time: f32| match time {
+        t if t < 0.5 => None,
+        t if t < 1.0 => Some(Some("b")),
+        t if t < 2.0 => Some(Some("c")),
+        t if t < 4.0 => Some(None),
+        _ => Some(Some("a")),
+    };
+    let times = (0..50)
+        .map(|i| i as f32 * 0.1)
+        .chain((0..50).map(|i| (i * 7 % 50) as f32 * 0.1))
+        .collect::<Vec<_>>();
+    let (mut cursor, mut cursors) = (Cursor::new(), [Cursor::new(); 7]);
+    for &time in &times {
+        assert_eq!(slot.interpolate_attachment(time), expected(time));
+        assert_eq!(
+            slot.interpolate_attachment_with_cursor(time, &mut cursor),
+            expected(time)
+        );
+        let srt = bone.srt(time);
+        let cursor_srt = bone.srt_with_cursors(time, &mut cursors);
+        assert_eq!(srt.rotation, cursor_srt.rotation);
+    }
+
+    // keys at the same time: the last one wins
+    assert!((bone.srt(1.0).rotation.to_degrees() - 10.0).abs() < 1e-4);
+    assert!((bone.srt(1.5).rotation.to_degrees() - 20.0).abs() < 1e-4);
+    assert!((bone.srt(9.0).rotation.to_degrees() - 40.0).abs() < 1e-4);
+    assert_eq!(bone.srt(0.2).rotation, 0.0);
+}
+
+#[test]
+fn baked_animation() {
+    use spine::skeleton::animation::bake::BakedAnimation;
+    use spine::skeleton::animation::pose::Pose;
+    use spine::skeleton::reduce::Tolerance;
+
+    let src: &[u8] = include_bytes!("example.json");
+    let doc = spine::skeleton::Skeleton::from_reader(BufReader::new(src)).unwrap();
+    let anim = doc.get_animated_skin("default", Some("walk")).unwrap();
+
+    let baked = anim.bake(30.0, None);
+    let frames = (anim.get_duration() * 30.0).ceil() as usize + 1;
+    assert!(baked.bones.iter().all(|t| t.frames.len() == frames));
+    let tolerance = Tolerance::default();
+    let reduced = anim.bake(30.0, Some(tolerance));
+    let samples = |b: &BakedAnimation| b.bones.iter().map(|t| t.frames.len()).sum::<usize>();
+    assert!(samples(&reduced) < samples(&baked));
+
+    let (mut pose, mut srts, mut reduced_srts) = (Pose::new(), Vec::new(), Vec::new());
+    for frame in 0..frames {
+        let time = frame as f32 / 30.0;
+        anim.sample(time, &mut pose);
+        baked.srts(time, &mut srts);
+        reduced.srts(time, &mut reduced_srts);
+        for ((srt, baked), reduced) in pose.srts().iter().zip(&srts).zip(&reduced_srts) {
+            for i in 0..2 {
+                assert!((srt.position[i] - baked.position[i]).abs() < 1e-3);
+                let error = (srt.position[i] - reduced.position[i]).abs();
+                assert!(error <= tolerance.translation + 1e-3);
+            }
+            assert!((srt.cos - reduced.cos).abs() < 1e-2);
+        }
+        for track in &baked.slots {
+            let sprite = anim
+                .interpolate(time.min(anim.get_duration()))
+                .unwrap()
+                .find(|s| Some(s.attachment) == track.attachment(baked.frame(time)));
+            assert!(sprite.is_some() || track.attachment(baked.frame(time)).is_none());
+        }
+    }
+
+    // no two color tinting in the example
+    assert!(baked.slots.iter().all(|t| t.dark_color(3.5).is_none()));
+
+    let json = serde_json::to_string(&reduced).unwrap();
+    let decoded: BakedAnimation = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, reduced);
+
+    // tracks with missing values play back without panicking
+    let mut truncated = baked.clone();
+    truncated.bones[0].values.truncate(2);
+    truncated.slots[0].colors.clear();
+    truncated.srts(anim.get_duration(), &mut srts);
+    assert_eq!(
+        truncated.slots[0].color(3.5),
+        spine::skeleton::color::Color::WHITE
+    );
+}
+
+#[test]
+fn bake_limits() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "animations": { "spin": { "bones": { "root": { "rotate": [
+            { "time": 0, "angle": 0 },
+            { "time": 1, "angle": 1e12 }
+        ] } } } },
+        "skins": { "default": {} }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", Some("spin")).unwrap();
+
+    // huge angles are wrapped in a single step
+    let baked = anim.bake(30.0, None);
+    assert_eq!(baked.bones[0].frames.len(), 31);
+
+    for &fps in &[0.0, -30.0, f32::NAN, f32::INFINITY, 1e30] {
+        let bake = panic::catch_unwind(AssertUnwindSafe(|| anim.bake(fps, None)));
+        assert!(bake.is_err(), "{} fps", fps);
+    }
+}
+
+#[test]
+fn keyframe_reduction() {
+    use spine::skeleton::reduce::{self, Tolerance};
+    use spine::skeleton::timeline::CurveEvaluation;
+
+    // motion capture like export: a key on every frame, an eased rotation and a linear move
+    let src = |version: &str, angle: &str| {
+        let keys = |f: &dyn Fn(f32) -> String| {
+            (0..=60)
+                .map(|i| f(i as f32 / 30.0))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let ease = |t: f32| 90.0 * (t / 2.0) * (t / 2.0) * (3.0 - t);
+        format!(
+            r#"{{
+            "skeleton": {{ "spine": "{}" }},
+            "bones": [{{ "name": "root" }}, {{ "name": "arm", "parent": "root", "x": 10 }}],
+            "slots": [{{ "name": "arm", "bone": "arm", "attachment": "arm" }}],
+            "skins": {{ "default": {{ "arm": {{ "arm": {{ "width": 10, "height": 10 }} }} }} }},
+            "animations": {{ "mocap": {{
+                "bones": {{
+                    "root": {{ "translate": [{}] }},
+                    "arm": {{ "rotate": [{}] }}
+                }},
+                "slots": {{ "arm": {{ "color": [{}] }} }}
+            }} }}
+        }}"#,
+            version,
+            keys(&|t| format!(r#"{{ "time": {}, "x": {}, "y": 5 }}"#, t, 20.0 * t)),
+            keys(&|t| format!(r#"{{ "time": {}, "{}": {} }}"#, t, angle, ease(t))),
+            keys(&|t| format!(r#"{{ "time": {}, "color": "ffffffff" }}"#, t)),
+        )
+    };
+
+    let tolerance = Tolerance::default();
+    for &(version, angle) in &[
+        ("3.7.94", "angle"),
+        ("3.8.99", "angle"),
+        ("4.1.17", "value"),
+    ] {
+        let src = src(version, angle);
+        let simplify = |fit: Option<CurveEvaluation>| {
+            let mut simplified = Vec::new();
+            let removed =
+                reduce::simplify_json(src.as_bytes(), &mut simplified, &tolerance, fit).unwrap();
+            (removed, String::from_utf8(simplified).unwrap())
+        };
+        let (linear_removed, linear) = simplify(None);
+        let (segments_removed, segments) = simplify(Some(CurveEvaluation::Segments));
+        let (exact_removed, exact) = simplify(Some(CurveEvaluation::Exact));
+        // translate and color keys are linear, the eased rotation is a single cubic curve
+        assert!(linear_removed > 2 * 59 && linear_removed < 3 * 59);
+        assert!(segments_removed > linear_removed);
+        assert_eq!(exact_removed, 3 * 59);
+
+        let original = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+        let original = original
+            .get_animated_skin("default", Some("mocap"))
+            .unwrap();
+        let simplified = [
+            (linear, CurveEvaluation::Segments),
+            (segments, CurveEvaluation::Segments),
+            (exact, CurveEvaluation::Exact),
+        ];
+        for &(ref simplified, curves) in &simplified {
+            let doc = SkeletonLoader::new()
+                .curve_evaluation(curves)
+                .load(simplified.as_bytes())
+                .unwrap();
+            let anim = doc.get_animated_skin("default", Some("mocap")).unwrap();
+            for i in 0..=120 {
+                let time = i as f32 / 60.0;
+                let sprite = anim.interpolate(time).unwrap().next().unwrap();
+                let expected = original.interpolate(time).unwrap().next().unwrap();
+                for j in 0..2 {
+                    let error = (sprite.srt.position[j] - expected.srt.position[j]).abs();
+                    assert!(
+                        error <= tolerance.translation + 1e-3,
+                        "{} {}",
+                        version,
+                        time
+                    );
+                }
+                let error = (sprite.srt.rotation - expected.srt.rotation)
+                    .abs()
+                    .to_degrees();
+                assert!(error <= tolerance.rotation + 1e-3, "{} {}", version, time);
+                assert_eq!(sprite.color.to_rgba8(), [255; 4]);
+            }
+        }
+    }
+}
+
+#[test]
+fn simplified_layout() {
+    use spine::skeleton::reduce::{self, Tolerance};
+
+    // members keep their order, only the removed key differs from the source
+    let src = r#"{
+        "skeleton": { "spine": "3.8.99", "hash": "x" },
+        "bones": [{ "name": "root", "y": 2.50, "x": 1 }],
+        "animations": { "walk": { "bones": { "root": { "translate": [
+            { "time": 0, "y": 0, "x": 0 },
+            { "time": 0.5, "y": 1, "x": 1 },
+            { "time": 1, "y": 2, "x": 2 }
+        ] } } } },
+        "skins": [{ "name": "default", "attachments": {} }]
+    }"#;
+    let mut simplified = Vec::new();
+    let removed =
+        reduce::simplify_json(src.as_bytes(), &mut simplified, &Tolerance::default(), None)
+            .unwrap();
+    assert_eq!(removed, 1);
+    assert_eq!(
+        String::from_utf8(simplified).unwrap(),
+        concat!(
+            r#"{"skeleton":{"spine":"3.8.99","hash":"x"},"bones":[{"name":"root","y":2.5,"x":1}],"#,
+            r#""animations":{"walk":{"bones":{"root":{"translate":["#,
+            r#"{"time":0,"y":0,"x":0},{"time":1,"y":2,"x":2}]}}}},"#,
+            r#""skins":[{"name":"default","attachments":{}}]}"#
+        )
+    );
+}
+
+#[test]
+fn software_rendering() {
+    use spine::skeleton::animation::pose::Pose;
+    use spine::skeleton::animation::render::{Framebuffer, Image};
+    use spine::skeleton::color::Color;
+
+    let src = r#"{
+        "bones": [{ "name": "root" }],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body", "color": "ff0000ff" },
+            { "name": "glow", "bone": "root", "attachment": "glow", "color": "00ff00ff", "blend": "additive" },
+            { "name": "shadow", "bone": "root", "attachment": "shadow", "color": "0000ff80" },
+            { "name": "hit", "bone": "root", "attachment": "hit" }
+        ],
+        "skins": { "default": {
+            "body": { "body": { "width": 10, "height": 10 } },
+            "glow": { "glow": { "x": 5, "width": 10, "height": 10 } },
+            "shadow": { "shadow": {
+                "type": "mesh", "hull": 4,
+                "uvs": [0, 1, 1, 1, 1, 0, 0, 0],
+                "triangles": [0, 1, 2, 2, 3, 0],
+                "vertices": [-4, -16, 4, -16, 4, -8, -4, -8]
+            } },
+            "hit": { "hit": { "type": "boundingbox", "vertexCount": 3, "vertices": [0, 0, 20, 0, 0, 20] } }
+        } }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", None).unwrap();
+    let mut pose = Pose::new();
+    anim.sample(0.0, &mut pose);
+
+    // white page, tinted by the slot colors
+    let page = [255u8; 2 * 2 * 4];
+    let mut target = Framebuffer::new(40, 40);
+    target.draw(&anim, &pose, &[Image::new(2, 2, &page)]);
+    let rgba = |x, y| target.pixel(x, y).unwrap().to_rgba8();
+
+    // skeleton origin at the center, y axis going up
+    assert_eq!(rgba(16, 20), [255, 0, 0, 255]);
+    assert_eq!(rgba(22, 20), [255, 255, 0, 255]);
+    assert_eq!(rgba(27, 20), [0, 255, 0, 255]);
+    assert_eq!(rgba(20, 33), [0, 0, 255, 128]);
+    assert_eq!(rgba(2, 2), [0, 0, 0, 0]);
+
+    // regions cover their 150 pixels, the translucent mesh its 64 pixels, each pixel once
+    let pixels = target.to_rgba8();
+    assert_eq!(pixels.len(), 40 * 40 * 4);
+    let count = |alpha| pixels.chunks(4).filter(|p| p[3] == alpha).count();
+    assert_eq!(count(255), 150);
+    assert_eq!(count(128), 64);
+    assert_eq!(count(0), 40 * 40 - 150 - 64);
+
+    // view placing the origin at the bottom left corner, twice bigger
+    let mut target = Framebuffer::new(40, 40).with_view([0.0, 40.0], 2.0);
+    target.clear(Color::BLACK);
+    target.draw(&anim, &pose, &[Image::new(2, 2, &page)]);
+    assert_eq!(target.pixel(5, 35).unwrap().to_rgba8(), [255, 255, 0, 255]);
+    assert_eq!(target.pixel(15, 35).unwrap().to_rgba8(), [0, 255, 0, 255]);
+    assert_eq!(target.pixel(25, 35).unwrap().to_rgba8(), [0, 0, 0, 255]);
+
+    // a texel per color, the first row at the top of the image
+    #[rustfmt::skip]
+    let page = [
+        255, 0, 0, 255,    0, 255, 0, 255,
+        0, 0, 255, 255,    255, 255, 255, 255,
+    ];
+    let render = |slot: &str, background: Color| {
+        let src = format!(
+            r#"{{
+            "bones": [{{ "name": "root" }}],
+            "slots": [{{ "name": "quad", "bone": "root", "attachment": "quad", {} }}],
+            "skins": {{ "default": {{ "quad": {{ "quad": {{ "width": 20, "height": 20 }} }} }} }}
+        }}"#,
+            slot
+        );
+        let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+        let anim = doc.get_animated_skin("default", None).unwrap();
+        let mut pose = Pose::new();
+        anim.sample(0.0, &mut pose);
+        let mut target = Framebuffer::new(40, 40);
+        target.clear(background);
+        target.draw(&anim, &pose, &[Image::new(2, 2, &page)]);
+        // pixels inside the quad corners, top left, top right, bottom left, bottom right
+        let corners = [(11, 11), (28, 11), (11, 28), (28, 28)];
+        let pixels = corners.map(|(x, y)| target.pixel(x, y).unwrap().to_rgba8());
+        (pixels, target.pixel(2, 2).unwrap().to_rgba8())
+    };
+    let gray = Color::new(0.5, 0.5, 0.5, 1.0);
+
+    // the image is not flipped, its top row drawn at the top of the quad
+    let (pixels, outside) = render(r#""color": "ffffffff""#, Color::BLACK);
+    assert_eq!(
+        pixels,
+        [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 255, 255]
+        ]
+    );
+    assert_eq!(outside, [0, 0, 0, 255]);
+
+    // destination multiplied by the texels
+    let (pixels, outside) = render(r#""blend": "multiply""#, gray);
+    assert_eq!(
+        pixels,
+        [
+            [128, 0, 0, 255],
+            [0, 128, 0, 255],
+            [0, 0, 128, 255],
+            [128, 128, 128, 255]
+        ]
+    );
+    assert_eq!(outside, [128, 128, 128, 255]);
+
+    // destination lightened by the texels
+    let (pixels, _) = render(r#""blend": "screen""#, gray);
+    assert_eq!(
+        pixels,
+        [
+            [255, 128, 128, 255],
+            [128, 255, 128, 255],
+            [128, 128, 255, 255],
+            [255, 255, 255, 255]
+        ]
+    );
+
+    // two color tinting, the dark color replacing the black parts of the texels
+    let (pixels, outside) = render(
+        r#""color": "ff8000ff", "dark": "0000ff""#,
+        Color::new(0.0, 0.0, 0.0, 0.0),
+    );
+    assert_eq!(
+        pixels,
+        [
+            [255, 0, 255, 255],
+            [0, 128, 255, 255],
+            [0, 0, 0, 255],
+            [255, 128, 0, 255]
+        ]
+    );
+    assert_eq!(outside, [0, 0, 0, 0]);
+}
+
+#[test]
+fn svg_export() {
+    use spine::skeleton::animation::svg::SvgOptions;
+
+    let src = r#"{
+        "bones": [
+            { "name": "root", "length": 10 },
+            { "name": "arm", "parent": "root", "x": 10, "rotation": 90, "length": 5 }
+        ],
+        "slots": [
+            { "name": "body", "bone": "root", "attachment": "body" },
+            { "name": "hand", "bone": "arm", "attachment": "hand" },
+            { "name": "hit", "bone": "root", "attachment": "hit" }
+        ],
+        "skins": { "default": {
+            "body": { "body": { "width": 10, "height": 4 } },
+            "hand": { "hand": {
+                "type": "mesh", "hull": 3,
+                "uvs": [0, 1, 1, 1, 0, 0],
+                "triangles": [0, 1, 2],
+                "vertices": [0, 0, 2, 0, 0, 2]
+            } },
+            "hit": { "hit": { "type": "boundingbox", "vertexCount": 3, "vertices": [0, 0, 4, 0, 0, 4] } }
+        } }
+    }"#;
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", None).unwrap();
+
+    let svg = anim.to_svg(0.0, &SvgOptions::new().image("body", "body&face.png"));
+    let lines = svg.lines().collect::<Vec<_>>();
+    // geometry from (-5, -2) to (10, 5), with the default margin
+    assert!(lines[0].contains(r#"viewBox="-15 -15 35 27""#));
+    let elements = lines
+        .iter()
+        .skip_while(|l| !l.starts_with("<g "))
+        .skip(1)
+        .take_while(|l| !l.starts_with("</g>"))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        elements,
+        vec![
+            r#"<image class="region" data-slot="body" data-attachment="body" href="body&amp;face.png" x="-1" y="-1" width="2" height="2" preserveAspectRatio="none" transform="matrix(5 0 0 2 0 0) scale(1 -1)"/>"#,
+            r#"<polygon class="region" data-slot="body" data-attachment="body" points="-5,-2 -5,2 5,2 5,-2"/>"#,
+            r#"<polygon class="mesh" data-slot="hand" data-attachment="hand" points="10,0 10,2 8,0"/>"#,
+            r#"<polygon class="bounding-box" data-slot="hit" data-attachment="hit" points="0,0 4,0 0,4"/>"#,
+            r#"<line class="bone" data-bone="root" x1="0" y1="0" x2="10" y2="0"/>"#,
+            r#"<line class="bone" data-bone="arm" x1="10" y1="0" x2="10" y2="5"/>"#,
+        ]
+    );
+
+    // filtered geometry
+    let options = SvgOptions::new()
+        .bones(false)
+        .meshes(false)
+        .bounding_boxes(false)
+        .margin(0.0);
+    let svg = anim.to_svg(0.0, &options);
+    assert!(svg.contains(r#"viewBox="-5 -2 10 4""#));
+    assert_eq!(svg.matches("<polygon").count(), 1);
+    assert!(!svg.contains("<line") && !svg.contains("<image"));
+
+    // geometry overflowing to infinity is skipped
+    let src = src.replace(r#""x": 10, "rotation": 90"#, r#""x": 10, "scaleX": 1e39"#);
+    let doc = spine::skeleton::Skeleton::from_reader(src.as_bytes()).unwrap();
+    let anim = doc.get_animated_skin("default", None).unwrap();
+    let svg = anim.to_svg(0.0, &SvgOptions::new());
+    assert!(!svg.contains("inf") && !svg.contains("NaN"));
+    assert!(svg.contains(r#"data-bone="root""#) && !svg.contains(r#"data-bone="arm""#));
+    assert!(!svg.contains(r#"data-slot="hand""#));
+    assert!(svg.contains(r#"viewBox="-15 -14 35 26""#));
+}